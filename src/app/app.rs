@@ -1,6 +1,22 @@
+use crate::app::frecency::Frecency;
+use crate::app::history::History;
+use crate::app::job::{Job, OutputFeed};
+use crate::app::layout::PaneLayout;
+use crate::app::tab::{Sort, Tab};
+use crate::app::tree::{self, Tree};
 use crate::configuration::configuration::read_config;
+use crate::plugin::plugin::Plugins;
+use crate::ui::display::preview::{Previewer, Previews};
 use crate::ui::display::{pane::get_du, pane::get_pwd};
-use crate::ui::input::{run_app::Command, stateful_list::StatefulList};
+use crate::ui::input::{
+    archive::ArchiveView,
+    custom::CustomCommand,
+    extract::Conflict,
+    mouse::{Areas, Region},
+    open::Opener,
+    run_app::Command,
+    stateful_list::StatefulList,
+};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
@@ -8,6 +24,10 @@ use ratatui::{
     widgets::{ListState, Widget},
 };
 use std::fs::{self, read_dir, File};
+use std::path::{Path, PathBuf};
+use std::process::Command as SysCommand;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 pub struct App {
     pub files: StatefulList<(String, String)>,
@@ -31,6 +51,87 @@ pub struct App {
     pub selected_files: Vec<String>,
     pub selected_dirs: Vec<String>,
     pub ops_menu: StatefulList<String>,
+    pub archive: Option<ArchiveView>,
+    pub jobs: Vec<Job>,
+    pub message: Option<String>,
+    pub extract_conflict: Conflict,
+    // what copying or moving to another pane or tab does with what's there
+    pub transfer_conflict: Conflict,
+    // sources compare staged and the copies they're newer than, those are
+    // overwritten whatever `transfer_conflict` says
+    pub compared: Vec<(PathBuf, PathBuf)>,
+    pub extract_dest: Option<String>,
+    pub external_metadata: bool,
+    pub openers: Vec<Opener>,
+    pub show_open_with: bool,
+    pub open_with: StatefulList<Opener>,
+    pub run_in_terminal: Option<SysCommand>,
+    pub show_output: bool,
+    pub output: Vec<String>,
+    pub output_title: String,
+    pub output_scroll: usize,
+    // where the command shown in the output popup is still printing to
+    pub output_feed: Option<Arc<Mutex<OutputFeed>>>,
+    pub custom_commands: Vec<CustomCommand>,
+    pub show_custom_menu: bool,
+    pub custom_menu: StatefulList<CustomCommand>,
+    pub pending_custom: Option<CustomCommand>,
+    pub plugins: Plugins,
+    pub previewers: Vec<Previewer>,
+    pub previews: Previews,
+    pub preview_timeout: Duration,
+    pub history: History,
+    pub show_recent: bool,
+    pub recent: StatefulList<String>,
+    pub frecency: Frecency,
+    pub frecency_import: Vec<String>,
+    pub show_jump: bool,
+    pub jump_results: StatefulList<String>,
+    // what `jump_results` were ranked for
+    pub jump_query: Option<String>,
+    // what `fzf_results` were searched for
+    pub fzf_query: Option<String>,
+    pub nav_suggestions: StatefulList<String>,
+    // what `nav_suggestions` were listed for
+    pub nav_query: Option<String>,
+    pub tabs: Vec<Tab>,
+    pub tab: usize,
+    // of the current tab, a case-insensitive part of the names to list
+    pub filter: String,
+    pub sort: Sort,
+    // where the copy and move entries after the first ones in `ops_menu` go
+    pub ops_destinations: Vec<(PathBuf, String)>,
+    pub layout: PaneLayout,
+    // the last `layout=` read, so switching layouts with a key sticks until
+    // the config changes
+    pub configured_layout: PaneLayout,
+    // the inactive side in the dual layout, the active side's state lives in
+    // `App` like the current tab's
+    pub other: Option<Tab>,
+    // whether the active side is the right one
+    pub active_right: bool,
+    // the current column of the miller layout, kept for its scroll offset
+    pub miller: ListState,
+    pub tree: Tree,
+    pub areas: Areas,
+    // for telling double clicks apart
+    pub last_click: Option<(Instant, Region, usize)>,
+    // how far the preview is scrolled down, and what it's scrolled for
+    pub preview_scroll: (String, usize),
+    // digits typed before a motion
+    pub count: Option<usize>,
+    // `g` was pressed, for `gg`, `gt` and `gT`
+    pub pending_g: bool,
+    // whether moving past the last entry goes back to the first
+    pub wrap: bool,
+    // the directory the listings were read in and when it was last modified
+    // then, they're only read again once either changes
+    pub listed: Option<(PathBuf, Option<SystemTime>)>,
+    // when config.txt was last read
+    pub config_modified: Option<SystemTime>,
+    // the miller layout's files and directories in one list, until the
+    // listings are read again
+    pub merged: Option<Vec<(String, bool)>>,
 }
 
 impl App {
@@ -56,6 +157,14 @@ impl App {
 
         let cur_dir = get_pwd();
         let cur_du = get_du();
+        let plugins = Plugins::load();
+        let message = plugins.error.clone();
+
+        let mut history = History::default();
+        let mut frecency = Frecency::load();
+        let cwd = std::env::current_dir().unwrap_or_default();
+        frecency.visit(&cwd);
+        history.visit(cwd.clone());
 
         App {
             files,
@@ -79,6 +188,62 @@ impl App {
             selected_files: vec![],
             selected_dirs: vec![],
             ops_menu: StatefulList::with_items(vec![]),
+            archive: None,
+            jobs: vec![],
+            message,
+            extract_conflict: Conflict::Skip,
+            transfer_conflict: Conflict::Skip,
+            compared: vec![],
+            extract_dest: None,
+            external_metadata: false,
+            openers: vec![],
+            show_open_with: false,
+            open_with: StatefulList::with_items(vec![]),
+            run_in_terminal: None,
+            show_output: false,
+            output: vec![],
+            output_title: String::new(),
+            output_scroll: 0,
+            output_feed: None,
+            custom_commands: vec![],
+            show_custom_menu: false,
+            custom_menu: StatefulList::with_items(vec![]),
+            pending_custom: None,
+            plugins,
+            previewers: vec![],
+            previews: Previews::default(),
+            preview_timeout: Duration::from_millis(500),
+            history,
+            show_recent: false,
+            recent: StatefulList::with_items(vec![]),
+            frecency,
+            frecency_import: vec![],
+            show_jump: false,
+            jump_results: StatefulList::with_items(vec![]),
+            jump_query: None,
+            fzf_query: None,
+            nav_suggestions: StatefulList::with_items(vec![]),
+            nav_query: None,
+            tabs: vec![Tab::new(cwd)],
+            tab: 0,
+            filter: String::new(),
+            sort: Sort::Name,
+            ops_destinations: vec![],
+            layout: PaneLayout::Default,
+            configured_layout: PaneLayout::Default,
+            other: None,
+            active_right: false,
+            miller: ListState::default(),
+            tree: Tree::default(),
+            areas: Areas::default(),
+            last_click: None,
+            preview_scroll: (String::new(), 0),
+            count: None,
+            pending_g: false,
+            wrap: true,
+            listed: None,
+            config_modified: None,
+            merged: None,
         }
     }
 
@@ -88,87 +253,217 @@ impl App {
         self.ops_menu.items.push("Clear selection".to_string());
     }
 
+    // drops finished jobs, keeping the error of any that failed to show
+    pub fn poll_jobs(&mut self) {
+        let mut i = 0;
+        while i < self.jobs.len() {
+            if !self.jobs[i].is_finished() {
+                i += 1;
+                continue;
+            }
+
+            let job = self.jobs.remove(i);
+            let name = job.name.clone();
+
+            let refresh = job.refresh;
+
+            if let Err(err) = job.join() {
+                self.message = Some(format!("{} failed: {}", name, err));
+            }
+
+            if refresh {
+                self.refresh();
+            }
+        }
+
+        self.poll_output();
+    }
+
+    // what the running command printed since the last frame
+    fn poll_output(&mut self) {
+        let feed = match &self.output_feed {
+            Some(feed) => feed.clone(),
+            None => return,
+        };
+        let mut feed = feed.lock().unwrap();
+
+        self.output.append(&mut feed.lines);
+
+        if let Some(status) = feed.status.take() {
+            self.output_title = format!("{} ({})", feed.title, status);
+            self.output_feed = None;
+        }
+    }
+
+    // after something outside traverse may have changed the disk
+    pub fn refresh(&mut self) {
+        self.update_listings();
+        if let Some(other) = &mut self.other {
+            other.listing = None;
+        }
+        self.cur_du = get_du();
+    }
+
     pub fn read_config(&mut self) {
         read_config(self);
     }
 
-    pub fn update_files(&mut self) {
+    // every frame, the listings are read again when something in the
+    // directory was added, removed or renamed, or the config changed.
+    // Everything else that changes them reads them itself
+    pub fn refresh_listing(&mut self) {
         self.read_config();
-        self.files.items.clear();
 
-        let mut file_entries: Vec<(String, String)> = vec![];
+        if self.listed == Some(listing_stamp()) {
+            return;
+        }
 
-        for entry in read_dir("./").unwrap() {
-            let entry = entry.unwrap();
-            if entry.metadata().unwrap().is_file() {
-                let temp = entry.file_name().into_string().unwrap();
-                if temp == "swapfile" {
-                    // previewing this file devastates the terminal,
-                    // mine anyway
-                    continue;
-                }
+        self.update_listings();
+        self.cur_dir = match &self.archive {
+            Some(archive) => archive.display_path(),
+            None => get_pwd(),
+        };
+    }
 
-                if temp.starts_with(".") && !self.show_hidden {
-                    continue;
-                }
+    // both listings, walking the tree only once for them
+    pub fn update_listings(&mut self) {
+        self.update_files();
 
-                file_entries.push((temp.clone(), temp));
-            }
+        if self.layout != PaneLayout::Tree || self.archive.is_some() {
+            self.update_dirs();
         }
+    }
+
+    // the tree's rows, files and directories with the paths from here as
+    // names. Both listings come from the same walk
+    fn update_tree(&mut self) {
+        self.tree.nodes = tree::nodes(self);
+
+        let (dirs, files): (Vec<&tree::Node>, Vec<&tree::Node>) =
+            self.tree.nodes.iter().partition(|node| node.is_dir);
+
+        self.files.items = files
+            .into_iter()
+            .map(|node| (node.path.clone(), node.path.clone()))
+            .collect();
+        self.dirs.items = vec![("../".to_string(), "../".to_string())];
+        self.dirs.items.extend(
+            dirs.into_iter()
+                .map(|node| (node.path.clone(), node.path.clone())),
+        );
+    }
 
-        file_entries.sort_by(|a, b| {
-            let a_starts_with_dot = a.0.starts_with(".");
-            let b_starts_with_dot = b.0.starts_with(".");
+    pub fn update_files(&mut self) {
+        self.read_config();
+        self.listed = Some(listing_stamp());
+        self.merged = None;
+        self.files.items.clear();
 
-            if a_starts_with_dot && !b_starts_with_dot {
-                std::cmp::Ordering::Greater
-            } else if !a_starts_with_dot && b_starts_with_dot {
-                std::cmp::Ordering::Less
-            } else {
-                a.0.cmp(&b.0)
+        if let Some(archive) = &self.archive {
+            for file in archive.files() {
+                if matches_filter(&self.filter, &file) {
+                    self.files.items.push((file.clone(), file));
+                }
             }
-        });
 
-        for file in file_entries {
-            self.files.items.push(file);
+            return;
         }
+
+        if self.layout == PaneLayout::Tree {
+            self.update_tree();
+            return;
+        }
+
+        let filter = self.filter.clone();
+        self.files.items = self.list_entries(Path::new("./"), false, &filter, self.sort);
     }
 
     pub fn update_dirs(&mut self) {
+        self.listed = Some(listing_stamp());
+        self.merged = None;
         self.dirs.items.clear();
         self.dirs.items.push(("../".to_string(), "../".to_string()));
 
-        let mut dir_entries: Vec<(String, String)> = vec![];
+        if let Some(archive) = &self.archive {
+            for dir in archive.dirs() {
+                if matches_filter(&self.filter, &dir) {
+                    self.dirs.items.push((dir.clone(), dir));
+                }
+            }
 
-        for entry in read_dir("./").unwrap() {
-            let entry = entry.unwrap();
+            return;
+        }
 
-            if entry.metadata().unwrap().is_dir() {
-                let temp = entry.file_name().into_string().unwrap();
+        if self.layout == PaneLayout::Tree {
+            self.update_tree();
+            return;
+        }
 
-                if temp.starts_with(".") && !self.show_hidden {
-                    continue;
-                }
+        let filter = self.filter.clone();
+        let dirs = self.list_entries(Path::new("./"), true, &filter, self.sort);
+        self.dirs.items.extend(dirs);
+    }
+
+    // the files, or directories, of `dir` in the order they're listed in,
+    // the other pane lists a directory that isn't the current one
+    pub fn list_entries(
+        &mut self,
+        dir: &Path,
+        dirs: bool,
+        filter: &str,
+        sort: Sort,
+    ) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = vec![];
+
+        let read = match read_dir(dir) {
+            Ok(read) => read,
+            Err(_) => return entries,
+        };
+
+        for entry in read.flatten() {
+            let wanted = match entry.metadata() {
+                Ok(metadata) if dirs => metadata.is_dir(),
+                Ok(metadata) => metadata.is_file(),
+                Err(_) => continue,
+            };
 
-                dir_entries.push((temp.clone(), temp.clone()));
+            if !wanted {
+                continue;
             }
-        }
 
-        dir_entries.sort_by(|a, b| {
-            let a_starts_with_dot = a.0.starts_with(".");
-            let b_starts_with_dot = b.0.starts_with(".");
+            let temp = entry.file_name().to_string_lossy().to_string();
+            if !dirs && temp == "swapfile" {
+                // previewing this file devastates the terminal,
+                // mine anyway
+                continue;
+            }
 
-            if a_starts_with_dot && !b_starts_with_dot {
-                std::cmp::Ordering::Greater
-            } else if !a_starts_with_dot && b_starts_with_dot {
-                std::cmp::Ordering::Less
-            } else {
-                a.0.cmp(&b.0)
+            if temp.starts_with(".") && !self.show_hidden {
+                continue;
             }
-        });
 
-        for dir in dir_entries {
-            self.dirs.items.push(dir);
+            if !matches_filter(filter, &temp) {
+                continue;
+            }
+
+            entries.push((temp.clone(), temp));
+        }
+
+        sort_listing(dir, sort, &mut entries);
+        self.plugin_sort(&mut entries);
+
+        entries
+    }
+
+    // a plugin's sorter, if one is registered, has the last word on order
+    pub fn plugin_sort(&mut self, entries: &mut Vec<(String, String)>) {
+        let names: Vec<String> = entries.iter().map(|entry| entry.0.clone()).collect();
+
+        if let Some(sorted) = self.plugins.sort(&names) {
+            *entries = sorted
+                .into_iter()
+                .map(|name| (name.clone(), name))
+                .collect();
         }
     }
 
@@ -194,6 +489,52 @@ impl App {
     }
 }
 
+fn listing_stamp() -> (PathBuf, Option<SystemTime>) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let modified = fs::metadata(&cwd)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    (cwd, modified)
+}
+
+pub fn matches_filter(filter: &str, name: &str) -> bool {
+    filter.is_empty() || name.to_lowercase().contains(&filter.to_lowercase())
+}
+
+// hidden entries last, otherwise by name, then in `sort`'s order
+pub fn sort_listing(dir: &Path, sort: Sort, entries: &mut [(String, String)]) {
+    entries.sort_by(|a, b| {
+        let a_starts_with_dot = a.0.starts_with(".");
+        let b_starts_with_dot = b.0.starts_with(".");
+
+        if a_starts_with_dot && !b_starts_with_dot {
+            std::cmp::Ordering::Greater
+        } else if !a_starts_with_dot && b_starts_with_dot {
+            std::cmp::Ordering::Less
+        } else {
+            a.0.cmp(&b.0)
+        }
+    });
+
+    sort_entries(dir, sort, entries);
+}
+
+// entries come sorted by name, the other orders keep that for ties
+fn sort_entries(dir: &Path, sort: Sort, entries: &mut [(String, String)]) {
+    let metadata = |name: &str| fs::metadata(dir.join(name)).ok();
+
+    match sort {
+        Sort::Name => {}
+        Sort::Size => entries.sort_by_key(|entry| {
+            std::cmp::Reverse(metadata(&entry.0).map(|m| m.len()).unwrap_or(0))
+        }),
+        Sort::Modified => entries.sort_by_key(|entry| {
+            std::cmp::Reverse(metadata(&entry.0).and_then(|m| m.modified().ok()))
+        }),
+    }
+}
+
 pub struct InputBox<'a> {
     text: &'a str,
     style: Style,
@@ -0,0 +1,366 @@
+use dirs::{config_dir, data_dir, home_dir};
+use std::fs::OpenOptions;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sublime_fuzzy::best_match;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+// visits are written out at most this often, and on quitting
+const SAVE_DELAY: Duration = Duration::from_secs(30);
+
+// once the ranks add up to this they're all scaled down, so directories
+// that stopped being visited eventually drop out
+const MAX_RANK: f64 = 10000.0;
+
+pub struct Entry {
+    pub path: PathBuf,
+    pub rank: f64,
+    // seconds since the epoch
+    pub last: u64,
+}
+
+impl Entry {
+    // weighted like zoxide, recent visits count for more
+    pub fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last);
+
+        let factor = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+
+        self.rank * factor
+    }
+}
+
+// visited directories, kept in `traverse/frecency.txt` as `rank|last|path`
+// lines, along with `#imported=<source>` for databases already imported
+#[derive(Default)]
+pub struct Frecency {
+    pub entries: Vec<Entry>,
+    pub imported: Vec<String>,
+    // imports that failed, they're only tried again next session
+    pub failed: Vec<String>,
+    // when the first visit that isn't saved yet was
+    changed: Option<Instant>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn db_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("traverse/frecency.txt"))
+}
+
+impl Frecency {
+    pub fn load() -> Frecency {
+        match db_path().and_then(|path| std::fs::read_to_string(path).ok()) {
+            Some(contents) => Frecency::parse(&contents),
+            None => Frecency::default(),
+        }
+    }
+
+    fn parse(contents: &str) -> Frecency {
+        let mut frecency = Frecency::default();
+
+        for line in contents.lines() {
+            if let Some(source) = line.strip_prefix("#imported=") {
+                frecency.imported.push(source.to_string());
+                continue;
+            }
+
+            // the path goes last since it may contain `|`
+            let mut split = line.splitn(3, '|');
+            let (rank, last, path) = match (split.next(), split.next(), split.next()) {
+                (Some(rank), Some(last), Some(path)) => (rank, last, path),
+                _ => continue,
+            };
+
+            if let (Ok(rank), Ok(last)) = (rank.parse(), last.parse()) {
+                frecency.entries.push(Entry {
+                    path: PathBuf::from(path),
+                    rank,
+                    last,
+                });
+            }
+        }
+
+        frecency
+    }
+
+    pub fn save(&self) {
+        let path = match db_path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+        {
+            let _ = file.write_all(self.contents().as_bytes());
+        }
+    }
+
+    fn contents(&self) -> String {
+        let mut contents = String::new();
+        for source in &self.imported {
+            contents.push_str(&format!("#imported={}\n", source));
+        }
+        for entry in &self.entries {
+            contents.push_str(&format!(
+                "{}|{}|{}\n",
+                entry.rank,
+                entry.last,
+                entry.path.display()
+            ));
+        }
+
+        contents
+    }
+
+    pub fn visit(&mut self, dir: &Path) {
+        self.add(dir, 1.0, now());
+        self.age();
+        self.changed.get_or_insert_with(Instant::now);
+    }
+
+    // saves visits once they've waited long enough, or straight away when
+    // quitting
+    pub fn flush(&mut self, quitting: bool) {
+        match self.changed {
+            Some(changed) if quitting || changed.elapsed() >= SAVE_DELAY => {
+                self.changed = None;
+                self.save();
+            }
+            _ => {}
+        }
+    }
+
+    fn add(&mut self, dir: &Path, rank: f64, last: u64) {
+        match self.entries.iter_mut().find(|entry| entry.path == dir) {
+            Some(entry) => {
+                entry.rank += rank;
+                entry.last = entry.last.max(last);
+            }
+            None => self.entries.push(Entry {
+                path: dir.to_path_buf(),
+                rank,
+                last,
+            }),
+        }
+    }
+
+    fn age(&mut self) {
+        let total: f64 = self.entries.iter().map(|entry| entry.rank).sum();
+
+        if total > MAX_RANK {
+            for entry in &mut self.entries {
+                entry.rank *= 0.9;
+            }
+            self.entries.retain(|entry| entry.rank >= 1.0);
+        }
+    }
+
+    // directories matching `query`, best first. The fuzzy score counts
+    // double when it matches the directory's own name rather than its parents
+    pub fn query(&self, query: &str, exclude: &Path) -> Vec<PathBuf> {
+        let now = now();
+        let query = query.trim();
+
+        let mut matches: Vec<(f64, &Entry)> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.path != exclude && entry.path.is_dir())
+            .filter_map(|entry| {
+                if query.is_empty() {
+                    return Some((entry.score(now), entry));
+                }
+
+                let name = entry
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let path = entry.path.to_string_lossy();
+
+                let fuzzy = match best_match(query, &name) {
+                    Some(matched) if matched.score() > 0 => matched.score() * 2,
+                    _ => best_match(query, &path)?.score(),
+                };
+
+                if fuzzy <= 0 {
+                    return None;
+                }
+
+                Some((entry.score(now) * fuzzy as f64, entry))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+        matches
+            .into_iter()
+            .map(|(_, entry)| entry.path.clone())
+            .collect()
+    }
+
+    // merges another tool's database, returning how many directories it had
+    pub fn import(&mut self, source: &str) -> Result<usize, String> {
+        let entries = match source {
+            "zoxide" => import_zoxide(),
+            "z" => import_z(),
+            "autojump" => import_autojump(),
+            _ => Err(format!("Unknown frecency import: {}", source)),
+        };
+
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.failed.push(source.to_string());
+                return Err(err);
+            }
+        };
+
+        let count = entries.len();
+        for (path, rank, last) in entries {
+            self.add(&path, rank, last);
+        }
+
+        self.imported.push(source.to_string());
+        self.changed = None;
+        self.save();
+
+        Ok(count)
+    }
+}
+
+// zoxide's database is binary, so it's asked for its scores instead
+fn import_zoxide() -> Result<Vec<(PathBuf, f64, u64)>, String> {
+    let output = Command::new("zoxide")
+        .args(["query", "--list", "--score"])
+        .output()
+        .map_err(|err| format!("Failed to run zoxide: {}", err))?;
+
+    let now = now();
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (rank, path) = line.trim().split_once(' ')?;
+            Some((PathBuf::from(path.trim()), rank.parse().ok()?, now))
+        })
+        .collect())
+}
+
+// `path|rank|time` lines in `$_Z_DATA` or `~/.z`
+fn import_z() -> Result<Vec<(PathBuf, f64, u64)>, String> {
+    let path = match std::env::var_os("_Z_DATA") {
+        Some(path) => PathBuf::from(path),
+        None => home_dir().unwrap_or_default().join(".z"),
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| {
+            let mut split = line.rsplitn(3, '|');
+            let (last, rank, path) = (split.next()?, split.next()?, split.next()?);
+            Some((PathBuf::from(path), rank.parse().ok()?, last.parse().ok()?))
+        })
+        .collect())
+}
+
+// `weight<tab>path` lines, with no record of when they were visited
+fn import_autojump() -> Result<Vec<(PathBuf, f64, u64)>, String> {
+    let path = data_dir().unwrap_or_default().join("autojump/autojump.txt");
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| {
+            let (rank, path) = line.split_once('\t')?;
+            Some((PathBuf::from(path), rank.parse().ok()?, 0))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_what_it_saves() {
+        let contents = "#imported=z\n12.5|1700000000|/home/me/a|b\nbroken line\nx|1|/nope\n3|1700000100|/srv\n";
+        let frecency = Frecency::parse(contents);
+
+        assert_eq!(frecency.imported, ["z"]);
+        assert_eq!(frecency.entries.len(), 2);
+        assert_eq!(frecency.entries[0].path, PathBuf::from("/home/me/a|b"));
+        assert_eq!(frecency.entries[0].rank, 12.5);
+        assert_eq!(frecency.entries[1].last, 1700000100);
+
+        assert_eq!(
+            frecency.contents(),
+            "#imported=z\n12.5|1700000000|/home/me/a|b\n3|1700000100|/srv\n"
+        );
+    }
+
+    #[test]
+    fn recent_visits_score_higher() {
+        let now = 10 * WEEK;
+        let entry = |age| Entry {
+            path: PathBuf::from("/"),
+            rank: 2.0,
+            last: now - age,
+        };
+
+        assert_eq!(entry(0).score(now), 8.0);
+        assert_eq!(entry(HOUR).score(now), 4.0);
+        assert_eq!(entry(DAY).score(now), 1.0);
+        assert_eq!(entry(WEEK).score(now), 0.5);
+        // from the future, like an imported database with a skewed clock
+        assert_eq!(entry(0).score(now - DAY), 8.0);
+    }
+
+    #[test]
+    fn visits_add_up_and_old_entries_age_out() {
+        let mut frecency = Frecency::default();
+        frecency.add(Path::new("/a"), 1.0, 5);
+        frecency.add(Path::new("/a"), 2.0, 3);
+        frecency.add(Path::new("/b"), MAX_RANK, 1);
+
+        assert_eq!(frecency.entries[0].rank, 3.0);
+        assert_eq!(frecency.entries[0].last, 5);
+
+        frecency.add(Path::new("/c"), 1.0, 1);
+        frecency.age();
+
+        let paths: Vec<&Path> = frecency.entries.iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/a"), Path::new("/b")]);
+        assert_eq!(frecency.entries[0].rank, 2.7);
+    }
+}
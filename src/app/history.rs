@@ -0,0 +1,34 @@
+use ratatui::widgets::ListState;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const RECENT_LIMIT: usize = 50;
+
+// the cursor in a directory, by name too since entries come and go
+#[derive(Clone)]
+pub struct Position {
+    pub files: ListState,
+    pub dirs: ListState,
+    pub file: Option<String>,
+    pub dir: Option<String>,
+}
+
+// directories visited this session, like a browser's back and forward
+#[derive(Default)]
+pub struct History {
+    pub back: Vec<PathBuf>,
+    pub forward: Vec<PathBuf>,
+    // where `leave` was called, until the directory change it was for is done
+    pub leaving: Option<PathBuf>,
+    pub positions: HashMap<PathBuf, Position>,
+    // most recent first
+    pub recent: Vec<PathBuf>,
+}
+
+impl History {
+    pub fn visit(&mut self, dir: PathBuf) {
+        self.recent.retain(|recent| *recent != dir);
+        self.recent.insert(0, dir);
+        self.recent.truncate(RECENT_LIMIT);
+    }
+}
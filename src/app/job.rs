@@ -0,0 +1,138 @@
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+// long running work (archiving, ...) kept off the ui thread,
+// `progress` is bumped by the worker and read by the gauge
+pub struct Job {
+    pub name: String,
+    // 0 until the worker knows how much there is
+    pub total: Arc<AtomicU64>,
+    pub progress: Arc<AtomicU64>,
+    // re-read listings and disk usage once done
+    pub refresh: bool,
+    handle: JoinHandle<io::Result<()>>,
+}
+
+impl Job {
+    pub fn spawn<F>(name: String, total: u64, work: F) -> Job
+    where
+        F: FnOnce(Arc<AtomicU64>) -> io::Result<()> + Send + 'static,
+    {
+        let job = Job::spawn_sizing(name, move |_, progress| work(progress));
+        job.total.store(total, Ordering::Relaxed);
+        job
+    }
+
+    // for work that has to find out how much there is first, off the ui
+    // thread too, it stores the total once it's known
+    pub fn spawn_sizing<F>(name: String, work: F) -> Job
+    where
+        F: FnOnce(Arc<AtomicU64>, Arc<AtomicU64>) -> io::Result<()> + Send + 'static,
+    {
+        let total = Arc::new(AtomicU64::new(0));
+        let progress = Arc::new(AtomicU64::new(0));
+        let (worker_total, worker_progress) = (total.clone(), progress.clone());
+
+        Job {
+            name,
+            total,
+            progress,
+            refresh: false,
+            handle: thread::spawn(move || work(worker_total, worker_progress)),
+        }
+    }
+
+    // none while there's no total to measure against
+    pub fn ratio(&self) -> Option<f64> {
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return None;
+        }
+
+        Some((self.progress.load(Ordering::Relaxed) as f64 / total as f64).min(1.0))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn join(self) -> io::Result<()> {
+        match self.handle.join() {
+            Ok(result) => result,
+            Err(_) => Err(io::Error::other("Job panicked")),
+        }
+    }
+}
+
+// what a command run for the output popup has printed so far, its job fills
+// it in and the ui thread takes the lines as they come
+#[derive(Default)]
+pub struct OutputFeed {
+    pub title: String,
+    pub lines: Vec<String>,
+    // how it ended, once it has
+    pub status: Option<String>,
+}
+
+impl OutputFeed {
+    // every line from `reader` until it's closed, not just valid UTF-8
+    pub fn read_from<R: Read>(feed: &Mutex<OutputFeed>, reader: R) {
+        let mut reader = BufReader::new(reader);
+        let mut line = vec![];
+
+        while matches!(reader.read_until(b'\n', &mut line), Ok(read) if read > 0) {
+            let text = String::from_utf8_lossy(&line)
+                .trim_end_matches(['\n', '\r'])
+                .to_string();
+
+            feed.lock().unwrap().lines.push(text);
+            line.clear();
+        }
+    }
+}
+
+// reader that reports how many bytes went through it
+pub struct ProgressReader<R> {
+    pub inner: R,
+    pub progress: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.fetch_add(read as u64, Ordering::Relaxed);
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn ratio_waits_for_the_total() {
+        let (sender, receiver) = mpsc::channel();
+        let job = Job::spawn_sizing("Sizing".to_string(), move |total, progress| {
+            receiver.recv().unwrap();
+            total.store(4, Ordering::Relaxed);
+            progress.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        });
+
+        assert_eq!(job.ratio(), None);
+
+        sender.send(()).unwrap();
+        while !job.is_finished() {
+            thread::yield_now();
+        }
+        assert_eq!(job.ratio(), Some(0.25));
+        assert!(job.join().is_ok());
+
+        let job = Job::spawn("Fixed".to_string(), 2, |_| Ok(()));
+        assert_eq!(job.ratio(), Some(0.0));
+    }
+}
@@ -0,0 +1,39 @@
+// how the browser is laid out, `layout=` in the config
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum PaneLayout {
+    // preview on the left, files and directories on the right
+    #[default]
+    Default,
+    // two independent browsers side by side, Midnight Commander style
+    Dual,
+    // parent, current directory with files and directories together, and
+    // preview, like ranger
+    Miller,
+    // preview on the left, the directory as a collapsible tree on the right
+    Tree,
+}
+
+impl PaneLayout {
+    pub fn from_config(value: &str) -> PaneLayout {
+        match value.trim().to_lowercase().as_str() {
+            "dual" => PaneLayout::Dual,
+            "miller" => PaneLayout::Miller,
+            "tree" => PaneLayout::Tree,
+            _ => PaneLayout::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_layout_from_the_config() {
+        assert!(PaneLayout::from_config(" Dual ") == PaneLayout::Dual);
+        assert!(PaneLayout::from_config("miller") == PaneLayout::Miller);
+        assert!(PaneLayout::from_config("TREE") == PaneLayout::Tree);
+        assert!(PaneLayout::from_config("default") == PaneLayout::Default);
+        assert!(PaneLayout::from_config("columns") == PaneLayout::Default);
+    }
+}
@@ -1 +1,7 @@
 pub mod app;
+pub mod frecency;
+pub mod history;
+pub mod job;
+pub mod layout;
+pub mod tab;
+pub mod tree;
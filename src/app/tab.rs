@@ -0,0 +1,76 @@
+use crate::app::history::Position;
+use crate::ui::input::archive::ArchiveView;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum Sort {
+    #[default]
+    Name,
+    // biggest first
+    Size,
+    // newest first
+    Modified,
+}
+
+impl Sort {
+    pub fn next(self) -> Sort {
+        match self {
+            Sort::Name => Sort::Size,
+            Sort::Size => Sort::Modified,
+            Sort::Modified => Sort::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Sort::Name => "name",
+            Sort::Size => "size",
+            Sort::Modified => "modified",
+        }
+    }
+}
+
+// a tab's directory as last read, with when the directory was modified then
+#[derive(Clone)]
+pub struct Listing {
+    pub modified: Option<SystemTime>,
+    pub files: Vec<(String, String)>,
+    pub dirs: Vec<(String, String)>,
+}
+
+// a tab that isn't the current one, the current one's state lives in `App`
+// and is only stored here when switching away
+pub struct Tab {
+    pub dir: PathBuf,
+    pub position: Option<Position>,
+    pub archive: Option<ArchiveView>,
+    pub filter: String,
+    pub sort: Sort,
+    pub back: Vec<PathBuf>,
+    pub forward: Vec<PathBuf>,
+    // kept for the dual layout's inactive side, which is shown every frame
+    pub listing: Option<Listing>,
+}
+
+impl Tab {
+    pub fn new(dir: PathBuf) -> Tab {
+        Tab {
+            dir,
+            position: None,
+            archive: None,
+            filter: String::new(),
+            sort: Sort::Name,
+            back: vec![],
+            forward: vec![],
+            listing: None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self.dir.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => self.dir.display().to_string(),
+        }
+    }
+}
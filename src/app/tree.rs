@@ -0,0 +1,81 @@
+use crate::app::app::App;
+use ratatui::widgets::ListState;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+// a row of the tree, `path` is relative to the current directory so the
+// file operations can use it like any other name
+pub struct Node {
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub is_dir: bool,
+    pub expanded: bool,
+}
+
+#[derive(Default)]
+pub struct Tree {
+    // absolute, so they stay expanded after going elsewhere and back
+    pub expanded: HashSet<PathBuf>,
+    pub state: ListState,
+    // the rows as of the last time the listings were read
+    pub nodes: Vec<Node>,
+}
+
+// the current directory's entries with the expanded directories' children
+// under them, directories first. Only expanded directories are read, and
+// the filter only applies to the top level
+pub fn nodes(app: &mut App) -> Vec<Node> {
+    let mut nodes = vec![];
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let filter = app.filter.clone();
+
+    add_children(app, &cwd, "", 0, &filter, &mut nodes);
+    nodes
+}
+
+fn add_children(
+    app: &mut App,
+    cwd: &Path,
+    prefix: &str,
+    depth: usize,
+    filter: &str,
+    nodes: &mut Vec<Node>,
+) {
+    let dir = cwd.join(prefix);
+    let sort = app.sort;
+
+    let dirs = app.list_entries(&dir, true, filter, sort);
+    let files = app.list_entries(&dir, false, filter, sort);
+
+    for (name, _) in dirs {
+        if app.excluded_directories.contains(&name) {
+            continue;
+        }
+
+        let path = format!("{}{}", prefix, name);
+        let expanded = app.tree.expanded.contains(&cwd.join(&path));
+
+        nodes.push(Node {
+            path: path.clone(),
+            name,
+            depth,
+            is_dir: true,
+            expanded,
+        });
+
+        if expanded {
+            add_children(app, cwd, &format!("{}/", path), depth + 1, "", nodes);
+        }
+    }
+
+    for (name, _) in files {
+        nodes.push(Node {
+            path: format!("{}{}", prefix, name),
+            name,
+            depth,
+            is_dir: false,
+            expanded: false,
+        });
+    }
+}
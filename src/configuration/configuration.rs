@@ -1,8 +1,14 @@
 use crate::app::app::App;
+use crate::app::layout::PaneLayout;
+use crate::ui::display::preview::Previewer;
+use crate::ui::input::custom::CustomCommand;
+use crate::ui::input::extract::Conflict;
+use crate::ui::input::open::Opener;
 use dirs::config_dir;
 use std::fs;
 use std::io::BufRead;
 use std::io::Write;
+use std::time::Duration;
 
 pub fn read_config(app: &mut App) {
     let config_path = config_dir().unwrap().join("traverse/config.txt");
@@ -25,10 +31,30 @@ pub fn read_config(app: &mut App) {
         writer
             .write_all(b"\nexcluded_directories=.git,.idea,.vscode,target")
             .unwrap();
+        writer.write_all(b"\nextract_conflict=skip").unwrap();
+        writer.write_all(b"\ntransfer_conflict=skip").unwrap();
+        writer.write_all(b"\nexternal_metadata=false").unwrap();
+        writer.write_all(b"\npreview_timeout=500").unwrap();
+        writer.write_all(b"\nwrap=true").unwrap();
     }
 
+    // it's read every frame, but only parsed again once it's been saved
+    let modified = fs::metadata(&config_path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if modified.is_some() && modified == app.config_modified {
+        return;
+    }
+    app.config_modified = modified;
+    app.listed = None;
+
     let file = fs::File::open(config_path).unwrap();
     let reader = std::io::BufReader::new(file);
+    app.excluded_directories.clear();
+    app.openers.clear();
+    app.custom_commands.clear();
+    app.previewers.clear();
+    app.frecency_import.clear();
 
     for line in reader.lines() {
         let line = line.unwrap();
@@ -58,5 +84,66 @@ pub fn read_config(app: &mut App) {
                 app.excluded_directories.push(value);
             }
         }
+
+        if let Some(value) = line.strip_prefix("extract_conflict=") {
+            app.extract_conflict = Conflict::from_config(value.trim());
+        }
+
+        if let Some(value) = line.strip_prefix("transfer_conflict=") {
+            app.transfer_conflict = Conflict::from_config(value.trim());
+        }
+
+        if let Some(value) = line.strip_prefix("external_metadata=") {
+            app.external_metadata = value.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Some(value) = line.strip_prefix("preview_timeout=") {
+            if let Ok(millis) = value.trim().parse() {
+                app.preview_timeout = Duration::from_millis(millis);
+            }
+        }
+
+        if let Some(value) = line.strip_prefix("frecency_import=") {
+            for source in value.to_lowercase().split(',') {
+                if !source.trim().is_empty() {
+                    app.frecency_import.push(source.trim().to_string());
+                }
+            }
+        }
+
+        if let Some(value) = line.strip_prefix("layout=") {
+            let layout = PaneLayout::from_config(value);
+
+            if layout != app.configured_layout {
+                app.configured_layout = layout;
+                app.layout = layout;
+            }
+        }
+
+        if let Some(value) = line.strip_prefix("wrap=") {
+            app.wrap = !value.trim().eq_ignore_ascii_case("false");
+        }
+
+        // commands can contain `=`, only split on the first one
+        if let Some(value) = line.strip_prefix("opener=") {
+            if let Some(opener) = Opener::parse(value) {
+                app.openers.push(opener);
+            }
+        }
+
+        if let Some(value) = line.strip_prefix("previewer=") {
+            if let Some(previewer) = Previewer::parse(value) {
+                app.previewers.push(previewer);
+            }
+        }
+
+        if let Some(value) = line.strip_prefix("command=") {
+            if let Some(command) = CustomCommand::parse(value) {
+                app.custom_commands.push(command);
+            }
+        }
     }
+
+    let plugin_commands = app.plugins.commands();
+    app.custom_commands.extend(plugin_commands);
 }
@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const CACHE_SIZE: usize = 64;
+
+struct Entry<K, T> {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    key: K,
+    value: T,
+}
+
+// what was worked out from a file or directory, kept until it's modified.
+// `key` is anything else the value depends on. Behind a lock since the
+// preview thread classifies files too
+pub struct FileCache<K, T> {
+    entries: Mutex<Vec<Entry<K, T>>>,
+}
+
+impl<K: PartialEq, T: Clone> FileCache<K, T> {
+    pub const fn new() -> FileCache<K, T> {
+        FileCache {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn get_or_insert_with(&self, path: &Path, key: K, make: impl FnOnce() -> T) -> T {
+        // relative names are the same in every directory
+        let path = std::env::current_dir().unwrap_or_default().join(path);
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        if let Some(entry) = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.path == path && entry.modified == modified && entry.key == key)
+        {
+            return entry.value.clone();
+        }
+
+        let value = make();
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| entry.path != path || entry.key != key);
+        entries.push(Entry {
+            path,
+            modified,
+            key,
+            value: value.clone(),
+        });
+
+        if entries.len() > CACHE_SIZE {
+            entries.remove(0);
+        }
+
+        value
+    }
+}
@@ -0,0 +1,217 @@
+use super::cache::FileCache;
+use crate::ui::input::extract::detect_format;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Category {
+    Text,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Executable,
+    Document,
+    Directory,
+    Other,
+}
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Text => "text",
+            Category::Image => "image",
+            Category::Audio => "audio",
+            Category::Video => "video",
+            Category::Archive => "archive",
+            Category::Executable => "executable",
+            Category::Document => "document",
+            Category::Directory => "directory",
+            Category::Other => "other",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FileType {
+    pub mime: String,
+    pub category: Category,
+}
+
+const ARCHIVES: [&str; 9] = [
+    "application/zip",
+    "application/x-tar",
+    "application/gzip",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/zstd",
+    "application/x-7z-compressed",
+    "application/vnd.rar",
+    "application/x-rar-compressed",
+];
+
+const EXECUTABLES: [&str; 6] = [
+    "application/x-executable",
+    "application/x-elf",
+    "application/x-sharedlib",
+    "application/x-mach-binary",
+    "application/vnd.microsoft.portable-executable",
+    "application/x-msdownload",
+];
+
+const DOCUMENTS: [&str; 5] = [
+    "application/pdf",
+    "application/msword",
+    "application/rtf",
+    "application/epub+zip",
+    "application/vnd.ms-excel",
+];
+
+const TEXTS: [&str; 6] = [
+    "application/json",
+    "application/xml",
+    "application/javascript",
+    "application/toml",
+    "application/x-sh",
+    "application/x-yaml",
+];
+
+static FILE_TYPES: FileCache<(), FileType> = FileCache::new();
+
+// the details pane asks every frame, the answer only changes with the file
+pub fn classify(path: &Path) -> FileType {
+    FILE_TYPES.get_or_insert_with(path, (), || classify_file(path))
+}
+
+fn classify_file(path: &Path) -> FileType {
+    if path.is_dir() {
+        return FileType {
+            mime: "inode/directory".to_string(),
+            category: Category::Directory,
+        };
+    }
+
+    let mut head = vec![];
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(8192).read_to_end(&mut head);
+    }
+
+    let mut file_type = classify_bytes(&path.to_string_lossy(), &head);
+
+    // magic bytes can't tell a tarball from any other compressed stream, but
+    // documents that are zips inside (docx, odt, epub) stay documents
+    if file_type.category == Category::Other && matches!(detect_format(path), Ok(Some(_))) {
+        file_type.category = Category::Archive;
+    }
+
+    file_type
+}
+
+// magic bytes win over the extension, except that content which is clearly
+// text stays text whatever the extension claims (`.ts` isn't always video)
+pub fn classify_bytes(name: &str, head: &[u8]) -> FileType {
+    let guess = mime_guess::from_path(name)
+        .first()
+        .map(|guess| guess.essence_str().to_string());
+
+    let mime = if let Some(kind) = infer::get(head) {
+        kind.mime_type().to_string()
+    } else if looks_like_text(head) {
+        match guess {
+            Some(guess) if category(&guess) == Category::Text || guess.ends_with("+xml") => guess,
+            _ => "text/plain".to_string(),
+        }
+    } else {
+        guess.unwrap_or_else(|| "application/octet-stream".to_string())
+    };
+
+    let category = category(&mime);
+
+    FileType { mime, category }
+}
+
+fn category(mime: &str) -> Category {
+    let (kind, subtype) = mime.split_once('/').unwrap_or((mime, ""));
+
+    if ARCHIVES.contains(&mime) {
+        Category::Archive
+    } else if EXECUTABLES.contains(&mime) {
+        Category::Executable
+    } else if DOCUMENTS.contains(&mime)
+        || subtype.starts_with("vnd.openxmlformats")
+        || subtype.starts_with("vnd.oasis.opendocument")
+    {
+        Category::Document
+    } else if kind == "text" || TEXTS.contains(&mime) || subtype.ends_with("+xml") {
+        Category::Text
+    } else if kind == "image" {
+        Category::Image
+    } else if kind == "audio" {
+        Category::Audio
+    } else if kind == "video" {
+        Category::Video
+    } else {
+        Category::Other
+    }
+}
+
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        // the sample may cut a multi-byte character in half
+        Err(err) if err.error_len().is_none() => true,
+        // Latin-1 and the like, mostly ASCII all the same
+        Err(_) => head.iter().filter(|byte| byte.is_ascii()).count() * 10 >= head.len() * 9,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    // a zip holding `name`, stored so its contents can be sniffed
+    fn write_zip(path: &Path, name: &str, contents: &[u8]) {
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        zip.start_file(name, options).unwrap();
+        zip.write_all(contents).unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn zip_based_documents_stay_documents() {
+        let dir = std::env::temp_dir().join(format!("traverse-{}-classify", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let epub = dir.join("book.epub");
+        write_zip(&epub, "mimetype", b"application/epub+zip");
+        assert_eq!(classify_file(&epub).category, Category::Document);
+
+        let zip = dir.join("notes.zip");
+        write_zip(&zip, "notes.txt", b"notes");
+        assert_eq!(classify_file(&zip).category, Category::Archive);
+
+        // no magic to go on, only the tar header's checksum
+        let tar = dir.join("old");
+        let mut header = tar::Header::new_old();
+        header.set_path("a.txt").unwrap();
+        header.set_size(1);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(File::create(&tar).unwrap());
+        builder.append(&header, &b"a"[..]).unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+        assert_eq!(classify_file(&tar).category, Category::Archive);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,8 @@
 mod app;
 mod ui;
 mod configuration;
+mod filetype;
+mod plugin;
 
 use ui::display::render::init;
 
@@ -0,0 +1,501 @@
+use crate::app::app::App;
+use crate::ui::display::pane::get_pwd;
+use crate::ui::input::custom::CustomCommand;
+use crate::ui::input::jump;
+use crate::ui::input::open::matches_pattern;
+use dirs::config_dir;
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+// keeps a runaway script from freezing the ui
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+// scripts can't leave the ui thread, so every preview they made is kept
+// until its file changes, going back and forth only runs them once
+const PREVIEW_CACHE_SIZE: usize = 32;
+
+struct CachedPreview {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    size: (u16, u16),
+    preview: String,
+}
+
+// what a script sees of the app, copied in before every call
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    pub cwd: String,
+    pub files: Vec<String>,
+    pub dirs: Vec<String>,
+    pub highlighted: String,
+    pub selection: Vec<String>,
+    pub bookmarks: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn from_app(app: &App) -> Snapshot {
+        let highlighted = app
+            .files
+            .state
+            .selected()
+            .and_then(|selected| app.files.items.get(selected))
+            .or_else(|| {
+                app.dirs
+                    .state
+                    .selected()
+                    .and_then(|selected| app.dirs.items.get(selected))
+            })
+            .map(|item| item.0.clone())
+            .unwrap_or_default();
+
+        Snapshot {
+            cwd: std::env::current_dir()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            files: app.files.items.iter().map(|item| item.0.clone()).collect(),
+            dirs: app.dirs.items.iter().map(|item| item.0.clone()).collect(),
+            highlighted,
+            selection: app.selected_files.clone(),
+            bookmarks: app.bookmarked_dirs.items.clone(),
+        }
+    }
+}
+
+// what a script asked for, applied to the app once it returns
+pub enum Effect {
+    Cd(String),
+    Select(String),
+    AddToSelection(String),
+    ClearSelection,
+    Message(String),
+    Refresh,
+}
+
+#[derive(Clone)]
+struct Action {
+    plugin: usize,
+    name: String,
+    function: String,
+    key: Option<char>,
+}
+
+struct Previewer {
+    plugin: usize,
+    pattern: String,
+    function: String,
+}
+
+struct Sorter {
+    plugin: usize,
+    function: String,
+}
+
+// shared between the engine's native functions and `Plugins`
+#[derive(Default)]
+struct Shared {
+    snapshot: Snapshot,
+    effects: Vec<Effect>,
+    loading: Option<usize>,
+    actions: Vec<Action>,
+    previewers: Vec<Previewer>,
+    sorters: Vec<Sorter>,
+}
+
+pub struct Plugins {
+    engine: Engine,
+    scripts: Vec<AST>,
+    shared: Rc<RefCell<Shared>>,
+    preview_cache: Vec<CachedPreview>,
+    sort_cache: HashMap<Vec<String>, Vec<String>>,
+    pub error: Option<String>,
+}
+
+impl Plugins {
+    // every `*.rhai` file in `<config_dir>/traverse/plugins`, in name order
+    pub fn load() -> Plugins {
+        let shared = Rc::new(RefCell::new(Shared::default()));
+        let mut plugins = Plugins {
+            engine: engine(&shared),
+            scripts: vec![],
+            shared,
+            preview_cache: vec![],
+            sort_cache: HashMap::new(),
+            error: None,
+        };
+
+        let dir = match config_dir() {
+            Some(dir) => dir.join("traverse/plugins"),
+            None => return plugins,
+        };
+
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map(|ext| ext == "rhai").unwrap_or(false))
+                .collect(),
+            Err(_) => return plugins,
+        };
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+
+            let ast = match plugins.engine.compile_file(path.clone()) {
+                Ok(ast) => ast,
+                Err(err) => {
+                    plugins.error = Some(format!("Plugin {} failed to load: {}", name, err));
+                    continue;
+                }
+            };
+
+            // the top level registers actions, previewers and sorters
+            plugins.shared.borrow_mut().loading = Some(plugins.scripts.len());
+            let result = plugins.engine.run_ast(&ast);
+            plugins.shared.borrow_mut().loading = None;
+
+            if let Err(err) = result {
+                plugins.error = Some(format!("Plugin {} failed to load: {}", name, err));
+            }
+
+            plugins.scripts.push(ast);
+        }
+
+        plugins
+    }
+
+    // listed alongside the config's custom commands
+    pub fn commands(&self) -> Vec<CustomCommand> {
+        self.shared
+            .borrow()
+            .actions
+            .iter()
+            .map(|action| CustomCommand {
+                key: action.key,
+                name: action.name.clone(),
+                template: action.function.clone(),
+                confirm: false,
+                output: false,
+                terminal: false,
+                refresh: false,
+                plugin: true,
+            })
+            .collect()
+    }
+
+    fn call(
+        &mut self,
+        plugin: usize,
+        function: &str,
+        snapshot: Snapshot,
+        args: Vec<Dynamic>,
+    ) -> (Result<Dynamic, String>, Vec<Effect>) {
+        self.shared.borrow_mut().snapshot = snapshot;
+
+        let result = self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.scripts[plugin], function, args)
+            .map_err(|err| err.to_string());
+
+        let effects = std::mem::take(&mut self.shared.borrow_mut().effects);
+        (result, effects)
+    }
+
+    // the first previewer whose pattern matches, like openers a MIME
+    // type (`image/*`), an extension (`.md`) or a glob on the name,
+    // it only gets its arguments and not the app's state
+    pub fn preview(&mut self, file: &str, mime: &str, width: u16, height: u16) -> Option<String> {
+        let (plugin, function) = {
+            let shared = self.shared.borrow();
+            let previewer = shared
+                .previewers
+                .iter()
+                .find(|previewer| matches_pattern(&previewer.pattern, file, mime))?;
+            (previewer.plugin, previewer.function.clone())
+        };
+
+        let path = std::fs::canonicalize(file).unwrap_or_else(|_| PathBuf::from(file));
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let size = (width, height);
+
+        if let Some(cached) = self.preview_cache.iter().find(|cached| {
+            cached.path == path && cached.modified == modified && cached.size == size
+        }) {
+            return Some(cached.preview.clone());
+        }
+
+        let args = vec![
+            Dynamic::from(file.to_string()),
+            Dynamic::from(width as i64),
+            Dynamic::from(height as i64),
+        ];
+
+        let preview = match self.call(plugin, &function, Snapshot::default(), args).0 {
+            Ok(preview) => preview.to_string(),
+            Err(err) => format!("Previewer {} failed: {}", function, err),
+        };
+
+        self.preview_cache.retain(|cached| cached.path != path);
+        self.preview_cache.push(CachedPreview {
+            path,
+            modified,
+            size,
+            preview: preview.clone(),
+        });
+
+        if self.preview_cache.len() > PREVIEW_CACHE_SIZE {
+            self.preview_cache.remove(0);
+        }
+
+        Some(preview)
+    }
+
+    // the last registered sorter reorders the names, anything that isn't
+    // the same names back is ignored
+    pub fn sort(&mut self, names: &[String]) -> Option<Vec<String>> {
+        let (plugin, function) = {
+            let shared = self.shared.borrow();
+            let sorter = shared.sorters.last()?;
+            (sorter.plugin, sorter.function.clone())
+        };
+
+        if let Some(sorted) = self.sort_cache.get(names) {
+            return Some(sorted.clone());
+        }
+
+        let args = vec![Dynamic::from_array(
+            names.iter().cloned().map(Dynamic::from).collect(),
+        )];
+
+        let sorted: Vec<String> = self
+            .call(plugin, &function, Snapshot::default(), args)
+            .0
+            .ok()?
+            .into_array()
+            .ok()?
+            .into_iter()
+            .filter_map(|name| name.into_string().ok())
+            .collect();
+
+        let mut expected = names.to_vec();
+        let mut got = sorted.clone();
+        expected.sort();
+        got.sort();
+
+        if expected != got {
+            return None;
+        }
+
+        if self.sort_cache.len() > 64 {
+            self.sort_cache.clear();
+        }
+        self.sort_cache.insert(names.to_vec(), sorted.clone());
+
+        Some(sorted)
+    }
+}
+
+fn strings(items: &[String]) -> Array {
+    items.iter().cloned().map(Dynamic::from).collect()
+}
+
+fn engine(shared: &Rc<RefCell<Shared>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    // state
+    let s = shared.clone();
+    engine.register_fn("cwd", move || s.borrow().snapshot.cwd.clone());
+    let s = shared.clone();
+    engine.register_fn("files", move || strings(&s.borrow().snapshot.files));
+    let s = shared.clone();
+    engine.register_fn("dirs", move || strings(&s.borrow().snapshot.dirs));
+    let s = shared.clone();
+    engine.register_fn("highlighted", move || {
+        s.borrow().snapshot.highlighted.clone()
+    });
+    let s = shared.clone();
+    engine.register_fn("selection", move || strings(&s.borrow().snapshot.selection));
+    let s = shared.clone();
+    engine.register_fn("bookmarks", move || strings(&s.borrow().snapshot.bookmarks));
+
+    // effects
+    let s = shared.clone();
+    engine.register_fn("cd", move |path: &str| {
+        s.borrow_mut().effects.push(Effect::Cd(path.to_string()))
+    });
+    let s = shared.clone();
+    engine.register_fn("select", move |name: &str| {
+        s.borrow_mut()
+            .effects
+            .push(Effect::Select(name.to_string()))
+    });
+    let s = shared.clone();
+    engine.register_fn("add_to_selection", move |path: &str| {
+        s.borrow_mut()
+            .effects
+            .push(Effect::AddToSelection(path.to_string()))
+    });
+    let s = shared.clone();
+    engine.register_fn("clear_selection", move || {
+        s.borrow_mut().effects.push(Effect::ClearSelection)
+    });
+    let s = shared.clone();
+    engine.register_fn("message", move |text: &str| {
+        s.borrow_mut()
+            .effects
+            .push(Effect::Message(text.to_string()))
+    });
+    let s = shared.clone();
+    engine.register_fn("refresh", move || {
+        s.borrow_mut().effects.push(Effect::Refresh)
+    });
+
+    // runs in the current directory, returning stdout
+    engine.register_fn("run", |command: &str| {
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    });
+
+    // registration, only while a plugin is loading
+    let s = shared.clone();
+    engine.register_fn("action", move |name: &str, function: &str| {
+        register_action(&s, name, function, None)
+    });
+    let s = shared.clone();
+    engine.register_fn("action", move |name: &str, function: &str, key: &str| {
+        register_action(&s, name, function, key.chars().next())
+    });
+    let s = shared.clone();
+    engine.register_fn("previewer", move |pattern: &str, function: &str| {
+        let mut shared = s.borrow_mut();
+        if let Some(plugin) = shared.loading {
+            shared.previewers.push(Previewer {
+                plugin,
+                pattern: pattern.to_string(),
+                function: function.to_string(),
+            });
+        }
+    });
+    let s = shared.clone();
+    engine.register_fn("sorter", move |function: &str| {
+        let mut shared = s.borrow_mut();
+        if let Some(plugin) = shared.loading {
+            shared.sorters.push(Sorter {
+                plugin,
+                function: function.to_string(),
+            });
+        }
+    });
+
+    engine
+}
+
+fn register_action(shared: &Rc<RefCell<Shared>>, name: &str, function: &str, key: Option<char>) {
+    let mut shared = shared.borrow_mut();
+    if let Some(plugin) = shared.loading {
+        shared.actions.push(Action {
+            plugin,
+            name: name.to_string(),
+            function: function.to_string(),
+            key,
+        });
+    }
+}
+
+// runs the action registered under `function`, from the custom command menu
+pub fn run_action(app: &mut App, function: &str) {
+    let action = app
+        .plugins
+        .shared
+        .borrow()
+        .actions
+        .iter()
+        .find(|action| action.function == function)
+        .cloned();
+
+    let action = match action {
+        Some(action) => action,
+        None => return,
+    };
+
+    let snapshot = Snapshot::from_app(app);
+    let (result, effects) = app
+        .plugins
+        .call(action.plugin, &action.function, snapshot, vec![]);
+
+    apply(app, effects);
+
+    if let Err(err) = result {
+        app.message = Some(format!("{} failed: {}", action.name, err));
+    }
+}
+
+fn apply(app: &mut App, effects: Vec<Effect>) {
+    for effect in effects {
+        match effect {
+            Effect::Cd(path) => {
+                jump::leave(app);
+
+                if let Err(err) = std::env::set_current_dir(&path) {
+                    app.message = Some(format!("Cannot open {}: {}", path, err));
+                    jump::stay(app);
+                    continue;
+                }
+
+                app.archive = None;
+                app.cur_dir = get_pwd();
+                app.update_files();
+                app.update_dirs();
+
+                if !jump::arrive(app) {
+                    app.files.state.select(Some(0));
+                    app.dirs.state.select(None);
+                }
+            }
+            Effect::Select(name) => {
+                let name = name.trim_end_matches('/');
+
+                if let Some(i) = app.files.items.iter().position(|item| item.0 == name) {
+                    app.files.state.select(Some(i));
+                    app.dirs.state.select(None);
+                } else if let Some(i) = app
+                    .dirs
+                    .items
+                    .iter()
+                    .position(|item| item.0.trim_end_matches('/') == name)
+                {
+                    app.dirs.state.select(Some(i));
+                    app.files.state.select(None);
+                }
+            }
+            Effect::AddToSelection(path) => {
+                let path = std::env::current_dir()
+                    .map(|dir| dir.join(&path).to_string_lossy().to_string())
+                    .unwrap_or(path);
+
+                if !app.selected_files.contains(&path) {
+                    app.selected_files.push(path);
+                }
+            }
+            Effect::ClearSelection => {
+                app.selected_files.clear();
+                app.selected_dirs.clear();
+            }
+            Effect::Message(text) => app.message = Some(text),
+            Effect::Refresh => app.refresh(),
+        }
+    }
+}
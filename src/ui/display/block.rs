@@ -8,6 +8,11 @@ pub fn block_binds(app: &mut App) -> bool {
         || app.show_popup
         || app.show_bookmark
         || app.show_ops_menu
+        || app.show_open_with
+        || app.show_output
+        || app.show_custom_menu
+        || app.show_recent
+        || app.show_jump
     {
         return true;
     }
@@ -1,4 +1,5 @@
 use crate::app::app::App;
+use crate::ui::input::mouse::Region;
 use crate::ui::input::nav::abbreviate_path;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
@@ -62,5 +63,11 @@ pub fn render_bookmark<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
             bookmark_list_area,
             &mut app.bookmarked_dirs.state,
         );
+        app.areas.add_list(
+            Region::Popup,
+            bookmark_list_area,
+            &app.bookmarked_dirs.state,
+            app.bookmarked_dirs.items.len(),
+        );
     }
 }
@@ -1,16 +1,15 @@
 use crate::app::app::App;
+use crate::ui::display::preview::{preview_lines, PreviewKey};
+use crate::ui::input::mouse::Region;
 use ratatui::backend::Backend;
 use ratatui::widgets::Paragraph;
 use ratatui::{
     layout::Rect,
     style::Style,
+    text::Text,
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
-use std::io::{Read, Seek, SeekFrom};
 
 pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]) {
     let contents_block = Block::default().borders(Borders::ALL).title("Preview");
@@ -18,64 +17,77 @@ pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Re
 
     let selected_file = match app.files.state.selected() {
         Some(i) => match app.files.items.get(i) {
-            Some(item) => &item.0,
-            None => "",
+            Some(item) => item.0.clone(),
+            None => String::new(),
         },
-        None => "",
+        None => String::new(),
     };
+    let selected_file = selected_file.as_str();
 
-    let mut content = String::new();
-    let max_lines = chunks[0].height as usize - 2;
+    let (width, height) = (
+        chunks[0].width.saturating_sub(2),
+        chunks[0].height.saturating_sub(2),
+    );
+    let mut text = Text::default();
 
     if !selected_file.is_empty() {
-        let metadata = match std::fs::metadata(selected_file) {
-            Ok(metadata) => metadata,
-            Err(err) => {
-                println!("Error getting metadata for file: {}", err);
-                return;
-            }
+        let key = match &app.archive {
+            Some(archive) => PreviewKey::new(
+                archive.path.clone(),
+                Some(format!("{}{}", archive.prefix, selected_file)),
+                width,
+                height,
+            ),
+            None => PreviewKey::new(
+                std::env::current_dir()
+                    .unwrap_or_default()
+                    .join(selected_file),
+                None,
+                width,
+                height,
+            ),
         };
 
-        if !metadata.is_file() {
-            println!("Not a regular file: {}", selected_file);
-            return;
-        }
-
-        let mut file = match File::open(selected_file) {
-            Ok(file) => file,
-            Err(err) => {
-                println!("Error opening file: {}", err);
-                return;
-            }
+        text = match app.previews.get(&key, &app.previewers, app.preview_timeout) {
+            Some(preview) => match preview.external {
+                Some(external) => external,
+                // plugins can't leave the ui thread, they only run once per
+                // file and size, see `Plugins::preview`
+                None if key.entry.is_none() && key.path.is_file() => app
+                    .plugins
+                    .preview(selected_file, &preview.mime, width, height)
+                    .map(|plugin| {
+                        Text::from(
+                            plugin
+                                .lines()
+                                .take(preview_lines(height))
+                                .collect::<Vec<&str>>()
+                                .join("\n"),
+                        )
+                    })
+                    .unwrap_or(preview.text),
+                None => preview.text,
+            },
+            None => Text::from("Loading…"),
         };
-
-        if is_binary(&mut file).unwrap_or(false) {
-            return;
-        }
-
-        let reader = BufReader::new(file);
-        for (num, line) in reader.lines().enumerate() {
-            if num >= max_lines {
-                break;
-            }
-
-            match line {
-                Ok(line) => {
-                    content.push_str(&line);
-                    content.push('\n');
-                }
-                #[allow(unused_variables)]
-                Err(err) => {
-                    continue;
-                }
-            }
-        }
     }
 
-    let items = List::new(vec![ListItem::new(content)])
+    // the wheel scrolls the preview, back to the top for another file
+    let file = format!("{}/{}", app.cur_dir, selected_file);
+    let scroll = if app.preview_scroll.0 == file {
+        app.preview_scroll.1.min(text.lines.len().saturating_sub(1))
+    } else {
+        0
+    };
+    app.preview_scroll = (file, scroll);
+    text.lines.drain(..scroll);
+    text.lines.truncate(height as usize);
+
+    let items = List::new(vec![ListItem::new(text)])
         .block(Block::default().borders(Borders::ALL).title("Preview"));
 
     f.render_stateful_widget(items, chunks[0], &mut app.files.state);
+    app.areas.add(Region::Preview, chunks[0]);
 
     if selected_file.is_empty() {
         let placeholder = Paragraph::new("No file selected")
@@ -84,16 +96,3 @@ pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Re
         f.render_widget(placeholder, chunks[0]);
     }
 }
-
-fn is_binary(file: &mut File) -> std::io::Result<bool> {
-    let mut buffer = vec![0; 1024];
-    file.read(&mut buffer)?;
-
-    let total_bytes = buffer.len();
-    let ascii_bytes = buffer.iter().filter(|b| b.is_ascii()).count();
-    let result = (ascii_bytes as f32 / total_bytes as f32) < 0.9;
-
-    file.seek(SeekFrom::Start(0))?;
-
-    Ok(result)
-}
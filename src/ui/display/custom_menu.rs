@@ -0,0 +1,21 @@
+use super::list_popup::{centered, render_list_popup};
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::{layout::Rect, Frame};
+
+pub fn render_custom_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_custom_menu {
+        let area = centered(size, f.size().width / 2, f.size().height / 3);
+        let items = app.custom_menu.items.iter().map(|i| i.label()).collect();
+
+        render_list_popup(
+            f,
+            &mut app.areas,
+            area,
+            "",
+            "Commands",
+            items,
+            &mut app.custom_menu.state,
+        );
+    }
+}
@@ -58,10 +58,27 @@ pub fn render_details<B: Backend>(
         None => "",
     };
 
-    let selected_item = if !selected_file.is_empty() {
-        selected_pane_content(&selected_file.to_string())
+    let selected_item = if let Some(archive) = &app.archive {
+        let name = if !selected_file.is_empty() {
+            selected_file
+        } else {
+            selected_dir
+        };
+
+        match archive.entry(name) {
+            Some(entry) if entry.is_dir => {
+                vec![ListItem::new(Spans::from("Directory in archive (read-only)"))]
+            }
+            Some(entry) => vec![ListItem::new(Spans::from(format!(
+                "{} bytes  (in archive, read-only)",
+                entry.size
+            )))],
+            None => vec![ListItem::new(Spans::from("No file selected"))],
+        }
+    } else if !selected_file.is_empty() {
+        selected_pane_content(&selected_file.to_string(), app.external_metadata)
     } else if !selected_dir.is_empty() {
-        selected_pane_content(&selected_dir.to_string())
+        selected_pane_content(&selected_dir.to_string(), app.external_metadata)
     } else {
         vec![ListItem::new(Spans::from("No file selected"))]
     };
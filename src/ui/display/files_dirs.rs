@@ -1,23 +1,71 @@
-use super::pane::get_pwd;
-use crate::app::app::App;
+use crate::app::app::{matches_filter, App};
+use crate::app::layout::PaneLayout;
+use crate::app::tab::Sort;
+use crate::ui::input::jump::restore_list;
+use crate::ui::input::mouse::Region;
+use crate::ui::input::panes::{other_listing, other_pane};
+use crate::ui::input::stateful_list::StatefulList;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
+use std::path::Path;
+
+// the directory when there are two sides, and the filter and order when
+// they're not the defaults
+pub fn title(name: &str, dir: Option<String>, sort: Sort, filter: &str) -> String {
+    let mut details = vec![];
+
+    if sort != Sort::Name {
+        details.push(format!("by {}", sort.label()));
+    }
+    if !filter.is_empty() {
+        details.push(format!("/{}", filter));
+    }
+
+    let name = match dir {
+        Some(dir) => format!("{}: {}", name, dir),
+        None => name.to_string(),
+    };
+
+    if details.is_empty() {
+        name
+    } else {
+        format!("{} ({})", name, details.join(", "))
+    }
+}
+
+fn current_title(name: &str, app: &App) -> String {
+    let dir = if app.layout != PaneLayout::Dual {
+        None
+    } else if let Some(archive) = &app.archive {
+        Some(archive.display_path())
+    } else {
+        Some(dir_name(&std::env::current_dir().unwrap_or_default()))
+    };
+
+    title(name, dir, app.sort, &app.filter)
+}
+
+pub fn dir_name(dir: &Path) -> String {
+    match dir.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => dir.display().to_string(),
+    }
+}
 
 pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]) {
+    let title = current_title("Files", app);
     let files_block = Block::default()
         .borders(Borders::ALL)
-        .title("Files")
+        .title(title.as_str())
         .title_alignment(Alignment::Center);
     f.render_widget(files_block, chunks[0]);
 
-    app.update_files();
-
     let files = app
         .files
         .items
@@ -29,7 +77,7 @@ pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Files")
+                .title(title.as_str())
                 .title_alignment(Alignment::Center),
         )
         .highlight_symbol("> ")
@@ -42,7 +90,7 @@ pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]
     if app.files.items.len() == 0 {
         let empty = vec![ListItem::new("No files in this directory")];
         let empty_list = List::new(empty)
-            .block(Block::default().borders(Borders::ALL).title("Files"))
+            .block(Block::default().borders(Borders::ALL).title(title.as_str()))
             .highlight_symbol("> ")
             .highlight_style(
                 Style::default()
@@ -50,22 +98,30 @@ pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]
                     .add_modifier(Modifier::BOLD),
             );
         f.render_stateful_widget(empty_list, chunks[0], &mut app.files.state);
+        app.areas
+            .add_list(Region::Files, chunks[0], &app.files.state, 1);
         return;
     }
 
     f.render_stateful_widget(items, chunks[0], &mut app.files.state);
+    app.areas.add_list(
+        Region::Files,
+        chunks[0],
+        &app.files.state,
+        app.files.items.len(),
+    );
 
     if app.files.state.selected().is_some() {
         let files_block = Block::default()
             .borders(Borders::ALL)
-            .title("Files")
+            .title(title.as_str())
             .title_alignment(Alignment::Center)
             .border_style(Style::default().fg(Color::LightBlue));
         f.render_widget(files_block, chunks[0]);
     } else {
         let files_block = Block::default()
             .borders(Borders::ALL)
-            .title("Files")
+            .title(title.as_str())
             .title_alignment(Alignment::Center)
             .border_style(Style::default().fg(Color::White));
         f.render_widget(files_block, chunks[0]);
@@ -73,11 +129,11 @@ pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]
 }
 
 pub fn render_dirs<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]) {
-    app.cur_dir = get_pwd();
+    let title = current_title("Directories", app);
 
     let dirs_block = Block::default()
         .borders(Borders::ALL)
-        .title("Directories")
+        .title(title.as_str())
         .title_alignment(Alignment::Center);
     f.render_widget(dirs_block, chunks[0]);
 
@@ -88,13 +144,11 @@ pub fn render_dirs<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect])
         .map(|i| ListItem::new(i.0.clone()))
         .collect::<Vec<ListItem>>();
 
-    app.update_dirs();
-
     let items = List::new(dirs)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Directories")
+                .title(title.as_str())
                 .title_alignment(Alignment::Center),
         )
         .highlight_symbol("> ")
@@ -105,20 +159,110 @@ pub fn render_dirs<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect])
         );
 
     f.render_stateful_widget(items, chunks[0], &mut app.dirs.state);
+    app.areas.add_list(
+        Region::Dirs,
+        chunks[0],
+        &app.dirs.state,
+        app.dirs.items.len(),
+    );
 
     if app.dirs.state.selected().is_some() {
         let dirs_block = Block::default()
             .borders(Borders::ALL)
-            .title("Directories")
+            .title(title.as_str())
             .title_alignment(Alignment::Center)
             .border_style(Style::default().fg(Color::LightBlue));
         f.render_widget(dirs_block, chunks[0]);
     } else {
         let dirs_block = Block::default()
             .borders(Borders::ALL)
-            .title("Directories")
+            .title(title.as_str())
             .title_alignment(Alignment::Center)
             .border_style(Style::default().fg(Color::White));
         f.render_widget(dirs_block, chunks[0]);
     }
 }
+
+// the inactive side of the dual layout, from its saved state
+pub fn render_other_pane<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let tab = other_pane(app);
+    let (dir, filter, sort) = (tab.dir.clone(), tab.filter.clone(), tab.sort);
+    let position = tab.position.clone();
+    let archive = tab.archive.as_ref().map(|archive| {
+        let listed = |names: Vec<String>| {
+            names
+                .into_iter()
+                .filter(|name| matches_filter(&filter, name))
+                .map(|name| (name.clone(), name))
+                .collect::<Vec<(String, String)>>()
+        };
+
+        (
+            archive.display_path(),
+            listed(archive.files()),
+            listed(archive.dirs()),
+        )
+    });
+
+    let (name, files, mut dirs) = match archive {
+        Some(listed) => listed,
+        None => {
+            let listing = other_listing(app);
+            (dir_name(&dir), listing.files, listing.dirs)
+        }
+    };
+    dirs.insert(0, ("../".to_string(), "../".to_string()));
+
+    let mut files = StatefulList::with_items(files);
+    let mut dirs = StatefulList::with_items(dirs);
+    match &position {
+        Some(position) => {
+            restore_list(&mut files, &position.files, &position.file);
+            restore_list(&mut dirs, &position.dirs, &position.dir);
+        }
+        None => files.state.select(Some(0)),
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let lists = [
+        (
+            Region::OtherFiles,
+            title("Files", Some(name.clone()), sort, &filter),
+            files,
+        ),
+        (
+            Region::OtherDirs,
+            title("Directories", Some(name), sort, &filter),
+            dirs,
+        ),
+    ];
+
+    for ((region, title, mut list), chunk) in lists.into_iter().zip(chunks.iter()) {
+        let items = if list.items.is_empty() {
+            vec![ListItem::new("No files in this directory")]
+        } else {
+            list.items
+                .iter()
+                .map(|i| ListItem::new(i.0.clone()))
+                .collect::<Vec<ListItem>>()
+        };
+
+        let len = items.len();
+        let items = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title.as_str())
+                    .title_alignment(Alignment::Center)
+                    .border_style(Style::default().fg(Color::DarkGray)),
+            )
+            .highlight_symbol("> ");
+
+        f.render_stateful_widget(items, *chunk, &mut list.state);
+        app.areas.add_new_list(region, *chunk, &list.state, len);
+    }
+}
@@ -40,24 +40,46 @@ ESC | q: Quit the application.
 1: Select the Files pane.
 2: Select the Directories pane.
 
-j: Select the next item in the current pane.
-k: Select the previous item in the current pane.
+j/k: Next/previous item, (a count first repeats it, 5j).
+gg/G: First/last item.  CTRL + d/u: Half a page.  PageDown/PageUp.
+H/M/L: Top/middle/bottom of the screen.  h/l: Parent/Enter.
 
 n: Create a new file or directory, depending on the current pane.
-CTRL + d: Delete the selected file or directory, (to bin).
+D | Delete: Delete the selected file or directory, (to bin).
 r: Rename the selected file or directory.
 
-f: Navigate to a directory using a relative or absolute path.
-x: Extract the selected archive, to the current directory.
+f: Navigate to a directory using a relative or absolute path, (Tab completes).
+Enter on a file: Open it.  o: Open with...
+e: Edit in $EDITOR.  s: Open $SHELL here.
+!: Run a command, ({} item, {s} buffer, {d} dir, !cmd suspends).
+m: Custom commands from the config and plugins.
+x: Extract the selected archive, into a new directory.
+Enter on an archive: Browse it, (x copies the selected entry out).
+a: Pack the buffer, or selected item, into an archive.
 w: Open fzf.
 
 c: Append the selected file or directory to the move/copy buffer.
 p: Opens the move/copy buffer menu, (enter on any option is in 
             relation to your current directory).
 
+t: New tab.  CTRL + w: Close tab.  Tab | gt/gT: Next/previous tab.  ALT + N: Tab N.
+/: Filter.  S: Sort by name, size or modified.
+
+|: Dual panes.  Tab: Other side.  F5/F6: Copy/Move there.
+=: Buffer what differs from the other side.
+Left/Right: Parent/Enter, (layout=miller).
+T: Tree.  Right/Left/Space: Expand/Collapse/Toggle.
+
 b: Shows bookmarks menu.
 z: Add current directory to bookmarks.
 
+[ / ALT + Left: Back.  ] / ALT + Right: Forward.
+': Recently visited directories.
+J: Jump to a frequently visited directory.
+
+Mouse: Click selects, double click opens, the wheel scrolls.
+Prompts: CTRL + w deletes a word, CTRL + u the line, Up/Down for history.
+
 CTRL + n: 'Next' item in results.
 CTRL + p: 'Previous' item in results.",
         );
@@ -1,4 +1,6 @@
 use crate::app::app::App;
+use crate::ui::input::line_editor::LineEditor;
+use crate::ui::input::run_app::Command;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::widgets::{Clear, Paragraph};
@@ -9,14 +11,57 @@ use ratatui::{
     Frame,
 };
 
-pub fn render_input<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect, input: &mut String) {
+// the part of the input that fits in `area`, scrolled to keep the cursor in
+// view, with the terminal cursor put there
+pub fn visible_input<B: Backend>(
+    f: &mut Frame<B>,
+    input: &LineEditor,
+    area: Rect,
+    masked: bool,
+) -> String {
+    let width = area.width.saturating_sub(2) as usize;
+    let column = input.cursor_column();
+    let skip = (column + 1).saturating_sub(width);
+
+    let text = if masked {
+        "*".repeat(input.chars().count())
+    } else {
+        input.to_string()
+    };
+
+    f.set_cursor(area.x + 1 + (column - skip) as u16, area.y + 1);
+    text.chars().skip(skip).take(width).collect()
+}
+
+pub fn render_input<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    size: Rect,
+    input: &mut LineEditor,
+) {
     if app.show_popup {
-        let block = Block::default()
-            .title("Name")
-            .borders(Borders::ALL)
-            .title_alignment(Alignment::Center);
+        let confirm = match &app.pending_custom {
+            Some(command) => format!("Run {}? (y/n)", command.name),
+            None => String::new(),
+        };
+
+        let title = match app.last_command {
+            Some(Command::CreateArchive) => "Archive (zip/tar.gz/xz/zst)",
+            Some(Command::ExtractArchive) => "Extract to",
+            Some(Command::ZipPassword) => "Password",
+            Some(Command::RunShell) => "Command ({} item, {s} buffer, {d} dir)",
+            Some(Command::ConfirmCustom) => confirm.as_str(),
+            Some(Command::Jump) => "Jump",
+            Some(Command::Filter) => "Filter (empty to clear)",
+            _ => "Input",
+        };
 
-        let input_box_width = 30;
+        // commands need the room
+        let input_box_width = if app.last_command == Some(Command::RunShell) {
+            size.width / 2
+        } else {
+            30
+        };
         let input_box_height = 3;
         let input_box_x = (size.width - input_box_width) / 4 + 3;
         let input_box_y = (size.height - input_box_height) / 1;
@@ -24,13 +69,15 @@ pub fn render_input<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect, inp
         let area = Rect::new(input_box_x, input_box_y, input_box_width, input_box_height);
 
         f.render_widget(Clear, area);
-        f.render_widget(block, area);
 
-        let input_box = Paragraph::new(input.clone())
+        let masked = app.last_command == Some(Command::ZipPassword);
+        let text = visible_input(f, input, area, masked);
+
+        let input_box = Paragraph::new(text)
             .style(Style::default())
             .block(
                 Block::default()
-                    .title("Input")
+                    .title(title)
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::LightBlue)),
             )
@@ -0,0 +1,48 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::{Clear, Gauge};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders},
+    Frame,
+};
+
+pub fn render_jobs<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    let gauge_width = size.width / 3;
+    let gauge_height = 3;
+
+    for (i, job) in app.jobs.iter().enumerate() {
+        let gauge_y = i as u16 * gauge_height;
+        if gauge_y + gauge_height > size.height {
+            break;
+        }
+
+        let area = Rect::new(size.width - gauge_width, gauge_y, gauge_width, gauge_height);
+
+        let ratio = job.ratio();
+        let mut gauge = Gauge::default()
+            .block(
+                Block::default()
+                    .title(job.name.clone())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::LightYellow))
+                    .title_alignment(Alignment::Center),
+            )
+            .gauge_style(
+                Style::default()
+                    .fg(Color::LightGreen)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .ratio(ratio.unwrap_or(0.0));
+
+        // shell commands, or a job still sizing up its work
+        if ratio.is_none() {
+            gauge = gauge.label("…");
+        }
+
+        f.render_widget(Clear, area);
+        f.render_widget(gauge, area);
+    }
+}
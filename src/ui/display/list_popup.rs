@@ -0,0 +1,68 @@
+use crate::ui::input::mouse::{Areas, Region};
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::{Clear, ListItem, ListState};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+// a `width` by `height` area in the middle of `size`
+pub fn centered(size: Rect, width: u16, height: u16) -> Rect {
+    Rect::new(
+        (size.width - width) / 2,
+        (size.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+// a popup holding one list to pick from, `title` goes on the outer block
+// and `list_title` on the list
+pub fn render_list_popup<B: Backend>(
+    f: &mut Frame<B>,
+    areas: &mut Areas,
+    area: Rect,
+    title: &str,
+    list_title: &str,
+    items: Vec<String>,
+    state: &mut ListState,
+) {
+    let block = Block::default()
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .title(title)
+        .border_style(
+            Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Center);
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let len = items.len();
+    let items: Vec<ListItem> = items.into_iter().map(ListItem::new).collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(list_title)
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::LightGreen),
+        )
+        .highlight_symbol("> ");
+
+    let list_area = Rect::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2);
+
+    f.render_stateful_widget(list, list_area, state);
+    areas.add_list(Region::Popup, list_area, state, len);
+}
@@ -0,0 +1,176 @@
+use crate::filetype::cache::FileCache;
+use crate::filetype::filetype::{Category, FileType};
+use exif::{In, Reader, Tag};
+use lofty::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+static DETAILS: FileCache<bool, Option<Vec<String>>> = FileCache::new();
+
+// details of images, audio and video read without any external tools,
+// `file`/`ffprobe` are only tried when `external` is set and parsing failed
+pub fn media_details(file: &Path, file_type: &FileType, external: bool) -> Option<Vec<String>> {
+    DETAILS.get_or_insert_with(file, external, || read_details(file, file_type, external))
+}
+
+fn read_details(file: &Path, file_type: &FileType, external: bool) -> Option<Vec<String>> {
+    let details = match file_type.category {
+        Category::Image => image_details(file),
+        Category::Audio => audio_details(file),
+        Category::Video => video_details(file).or_else(|| audio_details(file)),
+        _ => return None,
+    };
+
+    match details {
+        Some(details) => Some(details),
+        None if external => external_details(file, file_type.category == Category::Image),
+        None => Some(vec!["Cannot get details of file".to_string()]),
+    }
+}
+
+fn image_details(file: &Path) -> Option<Vec<String>> {
+    let size = imagesize::size(file).ok()?;
+    let mut details = vec![format!("{} x {} pixels", size.width, size.height)];
+
+    if let Ok(exif) = Reader::new().read_from_container(&mut BufReader::new(File::open(file).ok()?))
+    {
+        let camera = [Tag::Make, Tag::Model]
+            .iter()
+            .filter_map(|tag| exif.get_field(*tag, In::PRIMARY))
+            .map(|field| {
+                field
+                    .display_value()
+                    .to_string()
+                    .trim_matches('"')
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        if !camera.is_empty() {
+            details.push(camera);
+        }
+
+        let taken = [
+            Tag::DateTimeOriginal,
+            Tag::ExposureTime,
+            Tag::FNumber,
+            Tag::PhotographicSensitivity,
+        ]
+        .iter()
+        .filter_map(|tag| exif.get_field(*tag, In::PRIMARY))
+        .map(|field| field.display_value().with_unit(&exif).to_string())
+        .collect::<Vec<String>>()
+        .join("  ");
+
+        if !taken.is_empty() {
+            details.push(taken);
+        }
+    }
+
+    Some(details)
+}
+
+fn audio_details(file: &Path) -> Option<Vec<String>> {
+    let tagged = lofty::read_from_path(file).ok()?;
+    let properties = tagged.properties();
+
+    let mut details = vec![format!(
+        "{:?}  {}  {} kbps  {} Hz  {} ch",
+        tagged.file_type(),
+        format_duration(properties.duration()),
+        properties.audio_bitrate().unwrap_or(0),
+        properties.sample_rate().unwrap_or(0),
+        properties.channels().unwrap_or(0),
+    )];
+
+    if let Some(tag) = tagged.primary_tag().or_else(|| tagged.first_tag()) {
+        let fields = [tag.artist(), tag.title(), tag.album()]
+            .into_iter()
+            .flatten()
+            .map(|field| field.to_string())
+            .collect::<Vec<String>>();
+
+        if !fields.is_empty() {
+            details.push(format!("{}  ({:?})", fields.join(" - "), tag.tag_type()));
+        }
+    }
+
+    Some(details)
+}
+
+fn video_details(file: &Path) -> Option<Vec<String>> {
+    let reader = File::open(file).ok()?;
+    let size = reader.metadata().ok()?.len();
+    let mp4 = mp4::Mp4Reader::read_header(BufReader::new(reader), size).ok()?;
+
+    let mut details = vec![format!(
+        "{}  {}",
+        mp4.major_brand(),
+        format_duration(mp4.duration())
+    )];
+
+    let mut tracks = mp4.tracks().values().collect::<Vec<_>>();
+    tracks.sort_by_key(|track| track.track_id());
+
+    for track in tracks {
+        let codec = match track.box_type() {
+            Ok(codec) => codec.to_string(),
+            Err(_) => continue,
+        };
+
+        match track.track_type() {
+            Ok(mp4::TrackType::Video) => details.push(format!(
+                "video {}  {}x{}  {:.2} fps",
+                codec,
+                track.width(),
+                track.height(),
+                track.frame_rate()
+            )),
+            Ok(mp4::TrackType::Audio) => {
+                details.push(format!("audio {}  {} kbps", codec, track.bitrate() / 1000))
+            }
+            _ => {}
+        }
+    }
+
+    Some(details)
+}
+
+fn external_details(file: &Path, image: bool) -> Option<Vec<String>> {
+    let output = if image {
+        Command::new("file").arg(file).output()
+    } else {
+        Command::new("ffprobe")
+            .arg("-hide_banner")
+            .arg(file)
+            .output()
+    };
+
+    let output = match output {
+        Ok(output) => output,
+        Err(_) => return Some(vec!["Cannot get details of file".to_string()]),
+    };
+
+    // ffprobe writes its report to stderr
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+
+    Some(
+        String::from_utf8_lossy(&text)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect(),
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
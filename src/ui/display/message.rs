@@ -0,0 +1,39 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::{Clear, Paragraph, Wrap};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders},
+    Frame,
+};
+
+pub fn render_message<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if let Some(message) = &app.message {
+        let block_width = size.width / 2;
+        let block_height = 5;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        let message_para = Paragraph::new(message.clone())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Message (ESC to dismiss)")
+                    .border_style(
+                        Style::default()
+                            .fg(Color::LightRed)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title_alignment(Alignment::Center),
+            )
+            .wrap(Wrap { trim: true })
+            .alignment(Alignment::Center);
+
+        f.render_widget(Clear, area);
+        f.render_widget(message_para, area);
+    }
+}
@@ -0,0 +1,161 @@
+use super::contents;
+use super::files_dirs::{dir_name, title};
+use crate::app::app::App;
+use crate::ui::input::miller::{entries, listing, normalize};
+use crate::ui::input::mouse::Region;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+fn items(entries: &[(String, bool)]) -> Vec<ListItem<'static>> {
+    entries
+        .iter()
+        .map(|(name, is_dir)| {
+            if *is_dir {
+                ListItem::new(format!("{}/", name)).style(Style::default().fg(Color::LightBlue))
+            } else {
+                ListItem::new(name.clone())
+            }
+        })
+        .collect()
+}
+
+fn column<'a>(items: Vec<ListItem<'a>>, title: &'a str, color: Color) -> List<'a> {
+    List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_style(Style::default().fg(color)),
+        )
+        .highlight_symbol("> ")
+        .highlight_style(
+            Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        )
+}
+
+pub fn render_miller<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let ninety_percent = (area.height as f32 * 0.9) as u16;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
+        .split(area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(20),
+            Constraint::Percentage(40),
+            Constraint::Percentage(40),
+        ])
+        .split(chunks[0]);
+
+    render_parent(f, app, columns[0]);
+    let selected_dir = render_current(f, app, columns[1]);
+
+    match selected_dir {
+        Some(dir) => render_dir_preview(f, app, &dir, columns[2]),
+        None => contents::render_contents(f, app, &[columns[2]]),
+    }
+}
+
+// the directory holding this one, or holding the archive being browsed,
+// with this one highlighted
+fn render_parent<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+
+    let (dir, current) = match &app.archive {
+        Some(archive) => (Some(cwd.clone()), archive.path.file_name()),
+        None => (cwd.parent().map(|dir| dir.to_path_buf()), cwd.file_name()),
+    };
+    let current = current.map(|name| name.to_string_lossy().to_string());
+
+    let (name, entries) = match dir {
+        Some(dir) => (dir_name(&dir), listing(app, &dir)),
+        None => (String::new(), vec![]),
+    };
+
+    let mut state = ListState::default();
+    state.select(
+        entries
+            .iter()
+            .position(|entry| Some(&entry.0) == current.as_ref()),
+    );
+
+    f.render_stateful_widget(
+        column(items(&entries), &name, Color::White),
+        area,
+        &mut state,
+    );
+    app.areas
+        .add_new_list(Region::Parent, area, &state, entries.len());
+}
+
+// returns the highlighted directory, to preview its contents
+fn render_current<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) -> Option<String> {
+    let entries = entries(app);
+    let index = normalize(app, &entries);
+    app.miller.select(index);
+
+    let name = match &app.archive {
+        Some(archive) => archive.display_path(),
+        None => dir_name(&std::env::current_dir().unwrap_or_default()),
+    };
+    let title = title(&name, None, app.sort, &app.filter);
+
+    let len = entries.len().max(1);
+    let items = if entries.is_empty() {
+        vec![ListItem::new("Nothing in this directory")]
+    } else {
+        items(&entries)
+    };
+
+    f.render_stateful_widget(
+        column(items, &title, Color::LightBlue),
+        area,
+        &mut app.miller,
+    );
+    app.areas.add_list(Region::Current, area, &app.miller, len);
+
+    match index.and_then(|index| entries.get(index)) {
+        Some((name, true)) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn render_dir_preview<B: Backend>(f: &mut Frame<B>, app: &mut App, dir: &str, area: Rect) {
+    let entries = match &app.archive {
+        Some(archive) => {
+            let prefix = format!("{}{}/", archive.prefix, dir);
+
+            archive
+                .entries
+                .iter()
+                .filter_map(|entry| {
+                    let rest = entry.path.strip_prefix(&prefix)?.trim_end_matches('/');
+                    (!rest.is_empty() && !rest.contains('/'))
+                        .then(|| (rest.to_string(), entry.is_dir))
+                })
+                .collect()
+        }
+        None => listing(app, &std::env::current_dir().unwrap_or_default().join(dir)),
+    };
+
+    let items = if entries.is_empty() {
+        vec![ListItem::new("Nothing to preview")]
+    } else {
+        items(&entries)
+    };
+
+    f.render_widget(column(items, "Preview", Color::White), area);
+    app.areas.add(Region::Preview, area);
+}
@@ -1,11 +1,23 @@
 pub mod bookmarks;
 pub mod contents;
+pub mod custom_menu;
 pub mod details;
 pub mod files_dirs;
 pub mod inputs;
 pub mod navs;
+pub mod open_with;
 pub mod pane;
+pub mod preview;
+pub mod recent;
+pub mod tabs;
+pub mod tree;
 pub mod render;
 pub mod help;
 pub mod block;
 pub mod ops;
+pub mod jobs;
+pub mod list_popup;
+pub mod media;
+pub mod message;
+pub mod miller;
+pub mod output;
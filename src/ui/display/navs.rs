@@ -1,4 +1,8 @@
+use super::list_popup::{centered, render_list_popup};
 use crate::app::app::App;
+use crate::ui::display::inputs::visible_input;
+use crate::ui::input::line_editor::LineEditor;
+use crate::ui::input::mouse::Region;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::widgets::ListItem;
@@ -14,7 +18,7 @@ pub fn render_navigator<B: Backend>(
     f: &mut Frame<B>,
     app: &mut App,
     size: Rect,
-    input: &mut String,
+    input: &mut LineEditor,
 ) {
     if app.show_nav {
         let block = Block::default()
@@ -22,7 +26,8 @@ pub fn render_navigator<B: Backend>(
             .borders(Borders::ALL)
             .title_alignment(Alignment::Center);
 
-        let input_box_width = 30;
+        // paths need the room
+        let input_box_width = size.width / 2;
         let input_box_height = 3;
         let input_box_x = (size.width - input_box_width) / 4 + 3;
         let input_box_y = (size.height - input_box_height) / 1;
@@ -32,7 +37,8 @@ pub fn render_navigator<B: Backend>(
         f.render_widget(Clear, area);
         f.render_widget(block, area);
 
-        let input_box = Paragraph::new(input.clone())
+        let text = visible_input(f, input, area, false);
+        let input_box = Paragraph::new(text)
             .style(Style::default())
             .block(Block::default().title("Navigator").borders(Borders::ALL))
             .style(
@@ -42,6 +48,47 @@ pub fn render_navigator<B: Backend>(
             )
             .alignment(Alignment::Left);
         f.render_widget(input_box, area);
+
+        // matching directories, just above the input
+        if !app.nav_suggestions.items.is_empty() {
+            let dropdown_height = (app.nav_suggestions.items.len() as u16 + 2)
+                .min(12)
+                .min(input_box_y);
+            let dropdown_area = Rect::new(
+                input_box_x,
+                input_box_y - dropdown_height,
+                input_box_width,
+                dropdown_height,
+            );
+
+            let suggestions = app
+                .nav_suggestions
+                .items
+                .iter()
+                .map(|i| ListItem::new(i.clone()))
+                .collect::<Vec<ListItem>>();
+
+            let suggestions_list = List::new(suggestions)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Tab to complete")
+                        .border_style(Style::default().fg(Color::LightYellow)),
+                )
+                .highlight_style(
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .fg(Color::LightGreen),
+                )
+                .highlight_symbol("> ");
+
+            f.render_widget(Clear, dropdown_area);
+            f.render_stateful_widget(
+                suggestions_list,
+                dropdown_area,
+                &mut app.nav_suggestions.state,
+            );
+        }
     }
 }
 
@@ -93,5 +140,28 @@ pub fn render_fzf<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
             Rect::new(block_x + 1, block_y + 1, block_width - 2, block_height - 2);
 
         f.render_stateful_widget(results_list, results_list_area, &mut app.fzf_results.state);
+        app.areas.add_list(
+            Region::Popup,
+            results_list_area,
+            &app.fzf_results.state,
+            app.fzf_results.items.len(),
+        );
+    }
+}
+
+pub fn render_jump<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_jump {
+        let area = centered(size, f.size().width, f.size().height / 2);
+        let items = app.jump_results.items.clone();
+
+        render_list_popup(
+            f,
+            &mut app.areas,
+            area,
+            "Jump",
+            "Frecent",
+            items,
+            &mut app.jump_results.state,
+        );
     }
 }
@@ -0,0 +1,21 @@
+use super::list_popup::{centered, render_list_popup};
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::{layout::Rect, Frame};
+
+pub fn render_open_with<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_open_with {
+        let area = centered(size, f.size().width / 2, f.size().height / 3);
+        let items = app.open_with.items.iter().map(|i| i.label()).collect();
+
+        render_list_popup(
+            f,
+            &mut app.areas,
+            area,
+            "",
+            "Open with",
+            items,
+            &mut app.open_with.state,
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::app::app::App;
+use crate::ui::input::mouse::Region;
 use crate::ui::input::nav::abbreviate_path;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
@@ -67,6 +68,12 @@ pub fn render_ops_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
         );
 
         f.render_stateful_widget(ops_list, ops_menu_list_area, &mut app.ops_menu.state);
+        app.areas.add_list(
+            Region::Popup,
+            ops_menu_list_area,
+            &app.ops_menu.state,
+            app.ops_menu.items.len(),
+        );
 
         let mut selected_files_clone = app.selected_files.clone();
 
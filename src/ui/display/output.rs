@@ -0,0 +1,47 @@
+use crate::app::app::App;
+use crate::ui::input::mouse::Region;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders},
+    Frame,
+};
+
+// output of a `!` command, scrolled with j/k
+pub fn render_output<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_output {
+        let block_width = size.width * 3 / 4;
+        let block_height = size.height * 3 / 4;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        let text = if app.output.is_empty() {
+            "(no output)".to_string()
+        } else {
+            app.output.join("\n")
+        };
+
+        let output_para = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(app.output_title.clone())
+                    .border_style(
+                        Style::default()
+                            .fg(Color::LightYellow)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title_alignment(Alignment::Center),
+            )
+            .scroll((app.output_scroll as u16, 0));
+
+        f.render_widget(Clear, area);
+        f.render_widget(output_para, area);
+        app.areas.add(Region::Output, area);
+    }
+}
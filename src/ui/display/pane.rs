@@ -1,7 +1,11 @@
+use super::media::media_details;
+use crate::filetype::filetype::classify;
 use ratatui::{text::Spans, widgets::ListItem};
+use std::fs;
 use std::path::Path;
 use std::process::Command;
 use sysinfo::{DiskExt, System, SystemExt};
+use time::{OffsetDateTime, UtcOffset};
 
 #[allow(dead_code)]
 enum PaneState {
@@ -15,113 +19,157 @@ struct SelectedPane<T> {
     pub items: Vec<T>,
 }
 
-pub fn selected_pane_content(input: &String) -> Vec<ListItem<'static>> {
+pub fn selected_pane_content(input: &String, external: bool) -> Vec<ListItem<'static>> {
     let file = Path::new(&input);
 
-    if let Some(ext) = file.extension() {
-        if ext == "png" || ext == "jpg" {
-            let output = Command::new("file")
-                .arg(file)
-                .output()
-                .expect("failed to execute process");
+    if fs::symlink_metadata(file).is_ok() {
+        let file_type = classify(file);
+
+        let mut details = file_details(file);
+        let mut extra = vec![format!(
+            "{} ({})",
+            file_type.mime,
+            file_type.category.name()
+        )];
+        extra.extend(media_details(file, &file_type, external).unwrap_or_default());
+        details.splice(1..1, extra);
+
+        return details
+            .into_iter()
+            .map(|line| ListItem::new(Spans::from(line)))
+            .collect();
+    }
 
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let mut items = Vec::new();
+    vec![ListItem::new(Spans::from("No file selected"))]
+}
 
-            for line in output_str.lines() {
-                items.push(ListItem::new(Spans::from(line.to_string())));
-            }
+// most important first, the pane is only a few lines tall
+#[cfg(unix)]
+fn file_details(file: &Path) -> Vec<String> {
+    use std::os::unix::fs::MetadataExt;
 
-            return items;
-        }
+    let metadata = match fs::symlink_metadata(file) {
+        Ok(metadata) => metadata,
+        Err(_) => return vec!["No file selected".to_string()],
+    };
 
-        if ext == "mp4" || ext == "mp3" {
-            let output = Command::new("ffprobe")
-                .arg(file)
-                .output()
-                .expect("failed to execute process");
+    let owner = uzers::get_user_by_uid(metadata.uid())
+        .map(|user| user.name().to_string_lossy().to_string())
+        .unwrap_or_else(|| metadata.uid().to_string());
+    let group = uzers::get_group_by_gid(metadata.gid())
+        .map(|group| group.name().to_string_lossy().to_string())
+        .unwrap_or_else(|| metadata.gid().to_string());
+
+    let mut details = vec![format!(
+        "{}  {}:{}  {}  {}",
+        mode_string(&metadata, metadata.mode()),
+        owner,
+        group,
+        convert_bytes(metadata.len()),
+        format_time(metadata.mtime()),
+    )];
+
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(file)
+            .map(|target| target.display().to_string())
+            .unwrap_or_else(|_| "?".to_string());
+        details.push(format!("-> {}", target));
+    }
 
-            if output.stdout.is_empty() {
-                return vec![ListItem::new(Spans::from("Cannot get details of file"))];
-            }
+    details.push(format!(
+        "{:04o}  {} bytes  inode {}  links {}",
+        metadata.mode() & 0o7777,
+        metadata.len(),
+        metadata.ino(),
+        metadata.nlink(),
+    ));
+
+    let created = metadata
+        .created()
+        .ok()
+        .and_then(|created| created.duration_since(std::time::UNIX_EPOCH).ok())
+        .filter(|created| created.as_secs() > 0)
+        .map(|created| format_time(created.as_secs() as i64))
+        .unwrap_or_else(|| "-".to_string());
+
+    details.push(format!(
+        "accessed {}  changed {}  created {}",
+        format_time(metadata.atime()),
+        format_time(metadata.ctime()),
+        created,
+    ));
+
+    details
+}
 
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let mut items = Vec::new();
+#[cfg(not(unix))]
+fn file_details(file: &Path) -> Vec<String> {
+    let metadata = match fs::symlink_metadata(file) {
+        Ok(metadata) => metadata,
+        Err(_) => return vec!["No file selected".to_string()],
+    };
 
-            for line in output_str.lines() {
-                items.push(ListItem::new(Spans::from(line.to_string())));
-            }
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|modified| format_time(modified.as_secs() as i64))
+        .unwrap_or_else(|| "-".to_string());
+
+    vec![format!(
+        "{}  {}  {}",
+        if metadata.permissions().readonly() {
+            "read-only"
+        } else {
+            "read-write"
+        },
+        convert_bytes(metadata.len()),
+        modified,
+    )]
+}
 
-            return items;
-        }
+// `ls -l` style, e.g. drwxr-xr-x
+#[cfg(unix)]
+fn mode_string(metadata: &fs::Metadata, mode: u32) -> String {
+    let file_type = metadata.file_type();
+    let mut result = String::new();
+
+    result.push(if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_dir() {
+        'd'
+    } else if file_type.is_file() {
+        '-'
+    } else {
+        '?'
+    });
+
+    // user, group and other, each with the special bit shown in its execute slot
+    let triplets = [(0o4000, 's', 'S'), (0o2000, 's', 'S'), (0o1000, 't', 'T')];
+
+    for (i, (bit, set, unset)) in triplets.iter().enumerate() {
+        let shift = 6 - i * 3;
+        let bits = (mode >> shift) & 0o7;
+
+        result.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        result.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        result.push(match (bits & 0o1 != 0, mode & bit != 0) {
+            (true, true) => *set,
+            (false, true) => *unset,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
     }
 
-    if file.is_dir() {
-        let mut items = Vec::new();
-        let output = Command::new("ls")
-            .arg("-ld")
-            .arg(file)
-            .output()
-            .expect("failed to execute process");
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let output_vec = output_str.split_whitespace().collect::<Vec<&str>>();
-
-        let perms = output_vec[0];
-        let owner = output_vec[2];
-        let size = output_vec[4];
-        let date = output_vec[5];
-        let day = output_vec[6];
-        let time = output_vec[7];
-
-        if output.stdout.is_empty() {
-            return vec![ListItem::new(Spans::from("No directory selected"))];
-        }
-
-        #[allow(unused_variables)]
-        for line in output_str.lines() {
-            items.push(ListItem::new(Spans::from(format!(
-                "{}  {}  {}  {} {}/{}",
-                perms, owner, size, date, day, time
-            ))));
-        }
-
-        return items;
-    }
+    result
+}
 
-    if file.is_file() {
-        let mut items = Vec::new();
-        let output = Command::new("ls")
-            .arg("-lh")
-            .arg(file)
-            .output()
-            .expect("failed to execute process");
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let output_vec: Vec<&str> = output_str.split_whitespace().collect();
-
-        if output.stdout.is_empty() {
-            return vec![ListItem::new(Spans::from("No file selected"))];
-        }
-
-        let perms = output_vec[0];
-        let owner = output_vec[2];
-        let size = output_vec[4];
-        let date = output_vec[5];
-        let day = output_vec[6];
-        let time = output_vec[7];
-
-        #[allow(unused_variables)]
-        for line in output_str.lines() {
-            items.push(ListItem::new(Spans::from(format!(
-                "{}  {}  {}  {} {}/{}",
-                perms, owner, size, date, day, time
-            ))));
-        }
-        return items;
-    }
+fn format_time(timestamp: i64) -> String {
+    let offset = UtcOffset::try_current_local_offset().unwrap_or(UtcOffset::UTC);
 
-    vec![ListItem::new(Spans::from("No file selected"))]
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .to_offset(offset)
+        .format("%Y-%m-%d %H:%M")
 }
 
 pub fn get_pwd() -> String {
@@ -0,0 +1,621 @@
+use crate::filetype::filetype::{classify, classify_bytes, Category};
+use crate::ui::input::archive::{list_entries, read_entry};
+use crate::ui::input::open::{matches_pattern, quote};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans, Text};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+// a rule from `previewer=<pattern>|<command>` in the config, the command is
+// run with the file, pane width and height as $1, $2 and $3 (`{}` is the
+// file too) and whatever it prints is shown, colours included
+#[derive(Clone)]
+pub struct Previewer {
+    pub pattern: String,
+    pub command: String,
+}
+
+impl Previewer {
+    pub fn parse(value: &str) -> Option<Previewer> {
+        let (pattern, command) = value.split_once('|')?;
+        let pattern = pattern.trim().to_string();
+        let command = command.trim().to_string();
+
+        if pattern.is_empty() || command.is_empty() {
+            return None;
+        }
+
+        Some(Previewer { pattern, command })
+    }
+}
+
+// previews keep more lines than fit, so the wheel has something to scroll to
+const SCROLLBACK: usize = 1000;
+
+// most recently used last, small since a preview is up to `SCROLLBACK` lines
+const CACHE_SIZE: usize = 32;
+
+pub fn preview_lines(height: u16) -> usize {
+    (height as usize).max(SCROLLBACK)
+}
+
+// a file, or an entry of the archive being browsed, at a given mtime and
+// pane size, a preview only has to be generated again when any changes
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PreviewKey {
+    pub path: PathBuf,
+    pub entry: Option<String>,
+    pub modified: Option<SystemTime>,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl PreviewKey {
+    pub fn new(path: PathBuf, entry: Option<String>, width: u16, height: u16) -> PreviewKey {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        PreviewKey {
+            path,
+            entry,
+            modified,
+            width,
+            height,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Preview {
+    pub mime: String,
+    // from a `previewer=` rule, shown as is when set
+    pub external: Option<Text<'static>>,
+    pub text: Text<'static>,
+}
+
+// previews are generated on their own thread, the ui only ever looks in
+// the cache and shows a placeholder until the result comes back
+pub struct Previews {
+    cache: Vec<(PreviewKey, Preview)>,
+    pending: Option<(PreviewKey, Arc<AtomicBool>)>,
+    sender: Sender<(PreviewKey, Preview)>,
+    receiver: Receiver<(PreviewKey, Preview)>,
+}
+
+impl Default for Previews {
+    fn default() -> Previews {
+        let (sender, receiver) = mpsc::channel();
+
+        Previews {
+            cache: vec![],
+            pending: None,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Previews {
+    // the cached preview, or `None` while it's being generated, anything
+    // still in flight for another key is cancelled
+    pub fn get(
+        &mut self,
+        key: &PreviewKey,
+        previewers: &[Previewer],
+        timeout: Duration,
+    ) -> Option<Preview> {
+        while let Ok((done, preview)) = self.receiver.try_recv() {
+            if self.pending.as_ref().map(|(pending, _)| pending) == Some(&done) {
+                self.pending = None;
+            }
+            self.insert(done, preview);
+        }
+
+        if let Some(i) = self.cache.iter().position(|(cached, _)| cached == key) {
+            let entry = self.cache.remove(i);
+            let preview = entry.1.clone();
+            self.cache.push(entry);
+            return Some(preview);
+        }
+
+        if let Some((pending, cancel)) = &self.pending {
+            if pending == key {
+                return None;
+            }
+            cancel.store(true, Ordering::Relaxed);
+        }
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.pending = Some((key.clone(), cancel.clone()));
+
+        let key = key.clone();
+        let previewers = previewers.to_vec();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let preview = generate(&key, &previewers, timeout, &cancel);
+
+            if !cancel.load(Ordering::Relaxed) {
+                let _ = sender.send((key, preview));
+            }
+        });
+
+        None
+    }
+
+    fn insert(&mut self, key: PreviewKey, preview: Preview) {
+        self.cache.retain(|(cached, _)| *cached != key);
+        self.cache.push((key, preview));
+
+        if self.cache.len() > CACHE_SIZE {
+            self.cache.remove(0);
+        }
+    }
+}
+
+fn generate(
+    key: &PreviewKey,
+    previewers: &[Previewer],
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Preview {
+    let max_lines = preview_lines(key.height);
+
+    if let Some(entry) = &key.entry {
+        return archive_entry_preview(&key.path, entry, max_lines);
+    }
+
+    let file_type = classify(&key.path);
+    let file = key.path.to_string_lossy().to_string();
+
+    let external = previewers
+        .iter()
+        .find(|previewer| matches_pattern(&previewer.pattern, &file, &file_type.mime))
+        .and_then(|previewer| {
+            run_previewer(previewer, &file, key.width, key.height, timeout, cancel)
+        });
+
+    let text = if external.is_some() {
+        String::new()
+    } else if file_type.category == Category::Archive {
+        archive_listing(&key.path, max_lines)
+    } else if file_type.category == Category::Text && key.path.is_file() {
+        read_lines(&key.path, max_lines, cancel)
+    } else {
+        String::new()
+    };
+
+    Preview {
+        mime: file_type.mime,
+        external,
+        text: Text::from(text),
+    }
+}
+
+fn read_lines(file: &Path, max_lines: usize, cancel: &AtomicBool) -> String {
+    let file = match File::open(file) {
+        Ok(file) => file,
+        Err(_) => return String::new(),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut line = vec![];
+    let mut content = String::new();
+
+    for _ in 0..max_lines {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        // not all text is UTF-8
+        let text = String::from_utf8_lossy(&line);
+        content.push_str(text.trim_end_matches(['\n', '\r']));
+        content.push('\n');
+    }
+
+    content
+}
+
+fn archive_listing(file: &Path, max_lines: usize) -> String {
+    let entries = match list_entries(file) {
+        Ok(entries) => entries,
+        Err(_) => return String::from("Cannot read archive"),
+    };
+
+    let mut content = String::new();
+    for entry in entries.iter().take(max_lines) {
+        content.push_str(&entry.path);
+        if entry.is_dir {
+            content.push('/');
+        }
+        content.push('\n');
+    }
+
+    content
+}
+
+fn archive_entry_preview(archive: &Path, entry: &str, max_lines: usize) -> Preview {
+    let empty = Preview {
+        mime: String::new(),
+        external: None,
+        text: Text::default(),
+    };
+
+    let buffer = match read_entry(archive, entry, 64 * 1024) {
+        Ok(buffer) => buffer,
+        Err(_) => return empty,
+    };
+
+    let file_type = classify_bytes(entry, &buffer);
+    if buffer.is_empty() || file_type.category != Category::Text {
+        return Preview {
+            mime: file_type.mime,
+            ..empty
+        };
+    }
+
+    let mut content = String::new();
+    for line in String::from_utf8_lossy(&buffer).lines().take(max_lines) {
+        content.push_str(line);
+        content.push('\n');
+    }
+
+    Preview {
+        mime: file_type.mime,
+        external: None,
+        text: Text::from(content),
+    }
+}
+
+// `None` when the previewer had nothing to say, so the built-in preview
+// is used instead
+fn run_previewer(
+    previewer: &Previewer,
+    file: &str,
+    width: u16,
+    height: u16,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Option<Text<'static>> {
+    let script = previewer.command.replace("{}", &quote(file));
+
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(script)
+        .arg("sh")
+        .arg(file)
+        .arg(width.to_string())
+        .arg(height.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    // in a group of its own, so whatever it starts can be killed with it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command.spawn().ok()?;
+
+    // read on the side so a chatty previewer can't fill the pipe and stall
+    let mut stdout = child.stdout.take()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = stdout.read_to_end(&mut output);
+        let _ = sender.send(output);
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            // the selection moved on
+            Ok(None) if cancel.load(Ordering::Relaxed) => {
+                stop(&mut child);
+                return None;
+            }
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(5)),
+            _ => {
+                stop(&mut child);
+                return Some(Text::from(format!(
+                    "Previewer timed out after {} ms",
+                    timeout.as_millis()
+                )));
+            }
+        }
+    };
+
+    // something it left running can hold the pipe open after it's gone, the
+    // group is still around then so it's safe to kill, and what was printed
+    // before is kept
+    let output = match receiver.recv_timeout(timeout.saturating_sub(started.elapsed())) {
+        Ok(output) => output,
+        Err(_) => {
+            kill_group(child.id());
+            receiver
+                .recv_timeout(Duration::from_millis(100))
+                .unwrap_or_default()
+        }
+    };
+
+    if output.is_empty() && !status.success() {
+        return None;
+    }
+
+    Some(ansi_to_text(
+        &String::from_utf8_lossy(&output),
+        preview_lines(height),
+    ))
+}
+
+// the previewer and anything it started
+fn stop(child: &mut Child) {
+    kill_group(child.id());
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(pid as i32), libc::SIGKILL);
+    }
+}
+
+// SGR sequences become styles, any other escape sequence is dropped
+pub fn ansi_to_text(input: &str, max_lines: usize) -> Text<'static> {
+    let mut lines = vec![];
+    let mut style = Style::default();
+
+    for line in input.lines().take(max_lines) {
+        let mut spans = vec![];
+        let mut text = String::new();
+        let mut chars = line.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                // tabs would otherwise be drawn as nothing
+                if c == '\t' {
+                    text.push_str("    ");
+                } else if !c.is_control() {
+                    text.push(c);
+                }
+                continue;
+            }
+
+            match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut end = None;
+
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            end = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+
+                    if end == Some('m') {
+                        if !text.is_empty() {
+                            spans.push(Span::styled(std::mem::take(&mut text), style));
+                        }
+                        style = apply_sgr(style, &params);
+                    }
+                }
+                Some(']') => {
+                    // OSC, up to BEL or ESC \
+                    let mut escaped = false;
+                    for c in chars.by_ref() {
+                        if c == '\x07' || (escaped && c == '\\') {
+                            break;
+                        }
+                        escaped = c == '\x1b';
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !text.is_empty() {
+            spans.push(Span::styled(text, style));
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    Text::from(lines)
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = if params.is_empty() {
+        vec![0]
+    } else {
+        params
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect()
+    };
+
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            code @ 30..=37 => style = style.fg(indexed(code - 30)),
+            code @ 40..=47 => style = style.bg(indexed(code - 40)),
+            code @ 90..=97 => style = style.fg(indexed(code - 90 + 8)),
+            code @ 100..=107 => style = style.bg(indexed(code - 100 + 8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            code @ (38 | 48) => {
+                let color = match codes.get(i + 1) {
+                    Some(5) => {
+                        let color = codes.get(i + 2).map(|n| Color::Indexed(*n as u8));
+                        i += 2;
+                        color
+                    }
+                    Some(2) => {
+                        let color = match (codes.get(i + 2), codes.get(i + 3), codes.get(i + 4)) {
+                            (Some(r), Some(g), Some(b)) => {
+                                Some(Color::Rgb(*r as u8, *g as u8, *b as u8))
+                            }
+                            _ => None,
+                        };
+                        i += 4;
+                        color
+                    }
+                    _ => None,
+                };
+
+                if let Some(color) = color {
+                    style = if code == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    style
+}
+
+fn indexed(n: u16) -> Color {
+    match n {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // each line's spans as their text and style
+    fn spans(text: &Text) -> Vec<Vec<(String, Style)>> {
+        text.lines
+            .iter()
+            .map(|line| {
+                line.0
+                    .iter()
+                    .map(|span| (span.content.to_string(), span.style))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sgr_sequences_become_styles() {
+        let text = ansi_to_text("plain \x1b[1;31mbold red\x1b[22m red\x1b[0m done", 10);
+        let red = Style::default().fg(Color::Red);
+
+        assert_eq!(
+            spans(&text),
+            vec![vec![
+                ("plain ".to_string(), Style::default()),
+                ("bold red".to_string(), red.add_modifier(Modifier::BOLD)),
+                (
+                    " red".to_string(),
+                    red.remove_modifier(Modifier::BOLD | Modifier::DIM)
+                ),
+                (" done".to_string(), Style::default()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn extended_colours_carry_over_lines() {
+        let text = ansi_to_text("\x1b[38;5;208;48;2;1;2;3mone\ntwo\x1b[39;49m three", 10);
+        let style = Style::default()
+            .fg(Color::Indexed(208))
+            .bg(Color::Rgb(1, 2, 3));
+
+        assert_eq!(
+            spans(&text),
+            vec![
+                vec![("one".to_string(), style)],
+                vec![
+                    ("two".to_string(), style),
+                    (" three".to_string(), Style::default()),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn other_escapes_and_controls_are_dropped() {
+        let text = ansi_to_text(
+            "\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\\x1b[2K\tend\r",
+            10,
+        );
+
+        assert_eq!(
+            spans(&text),
+            vec![vec![("link    end".to_string(), Style::default())]]
+        );
+    }
+
+    #[test]
+    fn stops_at_max_lines() {
+        assert_eq!(ansi_to_text("a\nb\nc\nd", 2).lines.len(), 2);
+    }
+
+    #[test]
+    fn parses_previewer_rules() {
+        let previewer = Previewer::parse(" *.md | glow -s dark {} | head ").unwrap();
+        assert_eq!(previewer.pattern, "*.md");
+        assert_eq!(previewer.command, "glow -s dark {} | head");
+
+        assert!(Previewer::parse("*.md").is_none());
+        assert!(Previewer::parse("|glow").is_none());
+        assert!(Previewer::parse("*.md| ").is_none());
+    }
+}
@@ -0,0 +1,27 @@
+use super::list_popup::{centered, render_list_popup};
+use crate::app::app::App;
+use crate::ui::input::nav::abbreviate_path;
+use ratatui::backend::Backend;
+use ratatui::{layout::Rect, Frame};
+
+pub fn render_recent<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_recent {
+        let area = centered(size, f.size().width / 3, f.size().height / 3);
+        let items = app
+            .recent
+            .items
+            .iter()
+            .map(|i| abbreviate_path(i))
+            .collect();
+
+        render_list_popup(
+            f,
+            &mut app.areas,
+            area,
+            "",
+            "Recent",
+            items,
+            &mut app.recent.state,
+        );
+    }
+}
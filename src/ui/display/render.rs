@@ -1,9 +1,11 @@
 use crate::app::app::App;
+use crate::app::layout::PaneLayout;
 use crate::ui::display::*;
+use crate::ui::input::line_editor::LineEditor;
 use crate::ui::input::run_app::run_app;
 use anyhow::Result;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -21,7 +23,12 @@ pub fn init() -> Result<()> {
     enable_raw_mode()?;
 
     let stdout = io::stdout();
-    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture,)?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
@@ -36,6 +43,7 @@ pub fn init() -> Result<()> {
         terminal.backend_mut(),
         LeaveAlternateScreen,
         DisableMouseCapture,
+        DisableBracketedPaste,
     )?;
 
     terminal.show_cursor()?;
@@ -47,18 +55,88 @@ pub fn init() -> Result<()> {
     Ok(())
 }
 
-pub fn render<B: Backend>(f: &mut Frame<B>, app: &mut App, input: &mut String) {
+// hands the terminal to a child program (editor, pager, ...) and takes it
+// back afterwards, redrawing from scratch
+pub fn suspend<B: Backend, F: FnOnce() -> R, R>(terminal: &mut Terminal<B>, f: F) -> Result<R> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    terminal.show_cursor()?;
+
+    let result = f();
+
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+
+    Ok(result)
+}
+
+pub fn render<B: Backend>(f: &mut Frame<B>, app: &mut App, input: &mut LineEditor) {
+    app.refresh_listing();
+
     let cur_dir = app.cur_dir.clone();
     let cur_du = app.cur_du.clone();
 
     let size = f.size();
-    let fifty_percent = (size.width as f32 * 0.5) as u16;
-    let ninety_percent = (size.height as f32 * 0.9) as u16;
+    app.areas.clear();
+
+    // the tab bar only takes a line once there's more than one tab
+    let area = if app.tabs.len() > 1 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .split(size);
+
+        tabs::render_tabs(f, app, chunks[0]);
+        chunks[1]
+    } else {
+        size
+    };
+
+    let bottom_chunks = bottom_chunks(f, area);
+
+    match app.layout {
+        PaneLayout::Default => render_default(f, app, area),
+        PaneLayout::Dual => render_dual(f, app, area),
+        PaneLayout::Miller => miller::render_miller(f, app, area),
+        PaneLayout::Tree => render_tree(f, app, area),
+    }
+
+    details::render_details(f, app, &bottom_chunks, cur_dir, cur_du);
+    inputs::render_input(f, app, size, input);
+    navs::render_navigator(f, app, size, input);
+    navs::render_fzf(f, app, size);
+    navs::render_jump(f, app, size);
+    help::render_help(f, app, size);
+    bookmarks::render_bookmark(f, app, size);
+    ops::render_ops_menu(f, app, size);
+    open_with::render_open_with(f, app, size);
+    output::render_output(f, app, size);
+    custom_menu::render_custom_menu(f, app, size);
+    recent::render_recent(f, app, size);
+    jobs::render_jobs(f, app, size);
+    message::render_message(f, app, size);
+}
+
+fn render_default<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let fifty_percent = (area.width as f32 * 0.5) as u16;
+    let ninety_percent = (area.height as f32 * 0.9) as u16;
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Length(fifty_percent), Constraint::Min(1)])
-        .split(size);
+        .split(area);
 
     let left_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -74,22 +152,66 @@ pub fn render<B: Backend>(f: &mut Frame<B>, app: &mut App, input: &mut String) {
         ])
         .split(chunks[1]);
 
-    let bottom_chunks = bottom_chunks(f);
-
     contents::render_contents(f, app, &left_chunks);
     files_dirs::render_files(f, app, &[right_chunks[0]]);
     files_dirs::render_dirs(f, app, &[right_chunks[1]]);
-    details::render_details(f, app, &bottom_chunks, cur_dir, cur_du);
-    inputs::render_input(f, app, size, input);
-    navs::render_navigator(f, app, size, input);
-    navs::render_fzf(f, app, size);
-    help::render_help(f, app, size);
-    bookmarks::render_bookmark(f, app, size);
-    ops::render_ops_menu(f, app, size);
 }
 
-fn bottom_chunks<B: Backend>(f: &mut Frame<B>) -> Vec<Rect> {
-    let size = f.size();
+// the tree in place of the files and directories
+fn render_tree<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let fifty_percent = (area.width as f32 * 0.5) as u16;
+    let ninety_percent = (area.height as f32 * 0.9) as u16;
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(fifty_percent), Constraint::Min(1)])
+        .split(area);
+
+    let left_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
+        .split(chunks[0]);
+
+    let right_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
+        .split(chunks[1]);
+
+    tree::render_tree(f, app, right_chunks[0]);
+    contents::render_contents(f, app, &left_chunks);
+}
+
+// two browsers side by side above the details, with no preview
+fn render_dual<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let ninety_percent = (area.height as f32 * 0.9) as u16;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
+        .split(area);
+
+    let sides = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[0]);
+
+    let (active, other) = if app.active_right {
+        (sides[1], sides[0])
+    } else {
+        (sides[0], sides[1])
+    };
+
+    let active_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(active);
+
+    files_dirs::render_files(f, app, &[active_chunks[0]]);
+    files_dirs::render_dirs(f, app, &[active_chunks[1]]);
+    files_dirs::render_other_pane(f, app, other);
+}
+
+fn bottom_chunks<B: Backend>(f: &mut Frame<B>, size: Rect) -> Vec<Rect> {
     let ninety_percent = (size.height as f32 * 0.9) as u16;
 
     let chunks = Layout::default()
@@ -0,0 +1,43 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::Paragraph;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    Frame,
+};
+
+pub fn render_tabs<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+
+    let spans: Vec<Span> = app
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| {
+            // the current tab's slot isn't kept up to date
+            let name = if i == app.tab {
+                match cwd.file_name() {
+                    Some(name) => name.to_string_lossy().to_string(),
+                    None => cwd.display().to_string(),
+                }
+            } else {
+                tab.name()
+            };
+
+            let style = if i == app.tab {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightBlue)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+
+            Span::styled(format!(" {}:{} ", i + 1, name), style)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
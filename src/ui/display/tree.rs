@@ -0,0 +1,91 @@
+use super::files_dirs::{dir_name, title};
+use crate::app::app::App;
+use crate::ui::input::miller::normalize;
+use crate::ui::input::mouse::Region;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List, ListItem},
+    Frame,
+};
+
+pub fn render_tree<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    // archives are shown flat, they aren't on disk to be walked
+    let rows: Vec<(String, String, usize, Option<bool>)> = match &app.archive {
+        Some(_) => app
+            .dirs
+            .items
+            .iter()
+            .filter(|item| item.0 != "../")
+            .map(|item| (item.0.clone(), item.0.clone(), 0, Some(false)))
+            .chain(
+                app.files
+                    .items
+                    .iter()
+                    .map(|item| (item.0.clone(), item.0.clone(), 0, None)),
+            )
+            .collect(),
+        None => app
+            .tree
+            .nodes
+            .iter()
+            .map(|node| {
+                let expanded = node.is_dir.then_some(node.expanded);
+                (node.path.clone(), node.name.clone(), node.depth, expanded)
+            })
+            .collect(),
+    };
+
+    let entries: Vec<(String, bool)> = rows
+        .iter()
+        .map(|row| (row.0.clone(), row.3.is_some()))
+        .collect();
+    let index = normalize(app, &entries);
+    app.tree.state.select(index);
+
+    let items: Vec<ListItem> = if rows.is_empty() {
+        vec![ListItem::new("Nothing in this directory")]
+    } else {
+        rows.iter()
+            .map(|(_, name, depth, expanded)| {
+                let indent = "  ".repeat(*depth);
+
+                match expanded {
+                    Some(true) => ListItem::new(format!("{}▾ {}/", indent, name))
+                        .style(Style::default().fg(Color::LightBlue)),
+                    Some(false) => ListItem::new(format!("{}▸ {}/", indent, name))
+                        .style(Style::default().fg(Color::LightBlue)),
+                    None => ListItem::new(format!("{}  {}", indent, name)),
+                }
+            })
+            .collect()
+    };
+
+    let name = match &app.archive {
+        Some(archive) => archive.display_path(),
+        None => dir_name(&std::env::current_dir().unwrap_or_default()),
+    };
+    let title = title(&name, None, app.sort, &app.filter);
+
+    let len = items.len();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title.as_str())
+                .title_alignment(Alignment::Center)
+                .border_style(Style::default().fg(Color::LightBlue)),
+        )
+        .highlight_symbol("> ")
+        .highlight_style(
+            Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    f.render_stateful_widget(list, area, &mut app.tree.state);
+    app.areas
+        .add_list(Region::Current, area, &app.tree.state, len);
+}
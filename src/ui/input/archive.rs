@@ -0,0 +1,420 @@
+use crate::app::app::App;
+use crate::ui::display::pane::get_pwd;
+use crate::ui::input::extract::{
+    decoder, detect_format, extract_tar, extract_zip, Conflict, ExtractReport, Format,
+};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+// an archive opened as a read-only virtual directory,
+// `prefix` is the directory inside the archive currently shown
+pub struct ArchiveView {
+    pub path: PathBuf,
+    pub prefix: String,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl ArchiveView {
+    pub fn open(path: PathBuf) -> io::Result<ArchiveView> {
+        let entries = list_entries(&path)?;
+
+        Ok(ArchiveView {
+            path,
+            prefix: String::new(),
+            entries,
+        })
+    }
+
+    pub fn files(&self) -> Vec<String> {
+        self.children(false)
+    }
+
+    pub fn dirs(&self) -> Vec<String> {
+        self.children(true)
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&ArchiveEntry> {
+        let path = format!("{}{}", self.prefix, name);
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    pub fn display_path(&self) -> String {
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        format!("{}/{}", name, self.prefix)
+    }
+
+    fn children(&self, dirs: bool) -> Vec<String> {
+        let mut children: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.is_dir == dirs)
+            .filter_map(|e| e.path.strip_prefix(&self.prefix))
+            .filter(|rest| !rest.is_empty() && !rest.contains('/'))
+            .map(|rest| rest.to_string())
+            .collect();
+
+        children.sort();
+        children
+    }
+}
+
+pub fn is_archive(file: &str) -> bool {
+    matches!(
+        detect_format(Path::new(file)),
+        Ok(Some(Format::Zip | Format::Tar(_)))
+    )
+}
+
+fn open_tar(archive: &Path) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    match detect_format(archive)? {
+        Some(Format::Tar(compression)) => Ok(tar::Archive::new(decoder(
+            File::open(archive)?,
+            compression,
+        )?)),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Not an archive")),
+    }
+}
+
+pub fn list_entries(archive: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let mut entries = vec![];
+
+    if detect_format(archive)? == Some(Format::Zip) {
+        let mut zip = ZipArchive::new(File::open(archive)?)?;
+
+        for i in 0..zip.len() {
+            let file = zip.by_index_raw(i)?;
+            entries.push(ArchiveEntry {
+                path: normalise(file.name()),
+                size: file.size(),
+                is_dir: file.is_dir(),
+            });
+        }
+    } else {
+        let mut tar = open_tar(archive)?;
+
+        for entry in tar.entries()? {
+            let entry = entry?;
+            entries.push(ArchiveEntry {
+                path: normalise(&entry.path()?.to_string_lossy()),
+                size: entry.size(),
+                is_dir: entry.header().entry_type().is_dir(),
+            });
+        }
+    }
+
+    // tarballs often leave out directory entries, so fill in the
+    // parents of every entry to make the tree browsable
+    let mut implied = vec![];
+    for entry in &entries {
+        let mut path = entry.path.as_str();
+        while let Some(idx) = path.rfind('/') {
+            path = &path[..idx];
+            implied.push(path.to_string());
+        }
+    }
+
+    for dir in implied {
+        if !entries.iter().any(|e| e.path == dir) {
+            entries.push(ArchiveEntry {
+                path: dir,
+                size: 0,
+                is_dir: true,
+            });
+        }
+    }
+
+    entries.retain(|e| !e.path.is_empty());
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(entries)
+}
+
+// reads at most `limit` bytes of a single entry, for previews
+pub fn read_entry(archive: &Path, entry: &str, limit: u64) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![];
+
+    if detect_format(archive)? == Some(Format::Zip) {
+        let mut zip = ZipArchive::new(File::open(archive)?)?;
+
+        for i in 0..zip.len() {
+            let file = zip.by_index(i)?;
+            if normalise(file.name()) == entry {
+                file.take(limit).read_to_end(&mut buffer)?;
+                break;
+            }
+        }
+    } else {
+        let mut tar = open_tar(archive)?;
+
+        for file in tar.entries()? {
+            let file = file?;
+            if normalise(&file.path()?.to_string_lossy()) == entry {
+                file.take(limit).read_to_end(&mut buffer)?;
+                break;
+            }
+        }
+    }
+
+    Ok(buffer)
+}
+
+// copies `entry` (and everything below it when it's a directory) into
+// `dest`, keeping the paths relative to the entry's parent. It goes through
+// the same checks as extracting the whole archive
+pub fn extract_entry(
+    archive: &Path,
+    entry: &str,
+    dest: &Path,
+    conflict: Conflict,
+) -> io::Result<ExtractReport> {
+    let entry = Path::new(entry);
+    let strip = entry
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf);
+    let file = archive.to_string_lossy();
+
+    match detect_format(archive)? {
+        Some(Format::Zip) => extract_zip(&file, dest, strip, Some(entry), conflict, None),
+        Some(Format::Tar(compression)) => {
+            extract_tar(&file, compression, dest, strip, Some(entry), conflict)
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Not an archive")),
+    }
+}
+
+fn normalise(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+pub fn open_archive(app: &mut App) {
+    if let Some(selected) = app.files.state.selected() {
+        let file = app.files.items[selected].0.clone();
+        let path = std::env::current_dir().unwrap().join(file);
+
+        if let Ok(view) = ArchiveView::open(path) {
+            app.archive = Some(view);
+            app.cur_dir = app.archive.as_ref().unwrap().display_path();
+
+            app.update_files();
+            app.update_dirs();
+
+            app.files.state.select(None);
+            app.dirs.state.select(Some(0));
+        }
+    }
+}
+
+pub fn close_archive(app: &mut App) {
+    app.archive = None;
+    app.cur_dir = get_pwd();
+
+    app.update_files();
+    app.update_dirs();
+
+    app.files.state.select(None);
+    app.dirs.state.select(Some(0));
+}
+
+pub fn handle_archive_submit(app: &mut App) {
+    let selected = match app.dirs.state.selected() {
+        Some(selected) => app.dirs.items[selected].0.clone(),
+        None => return,
+    };
+
+    if selected == "../" && app.archive.as_ref().unwrap().prefix.is_empty() {
+        close_archive(app);
+        return;
+    }
+
+    let view = app.archive.as_mut().unwrap();
+
+    if selected == "../" {
+        view.prefix.pop();
+        match view.prefix.rfind('/') {
+            Some(idx) => view.prefix.truncate(idx + 1),
+            None => view.prefix.clear(),
+        }
+    } else {
+        view.prefix.push_str(&selected);
+        view.prefix.push('/');
+    }
+
+    app.cur_dir = view.display_path();
+    app.update_files();
+    app.update_dirs();
+    app.dirs.state.select(Some(0));
+}
+
+pub fn extract_selected(app: &mut App) {
+    let view = app.archive.as_ref().unwrap();
+
+    let name = if let Some(selected) = app.files.state.selected() {
+        match app.files.items.get(selected) {
+            Some(item) => item.0.clone(),
+            None => return,
+        }
+    } else if let Some(selected) = app.dirs.state.selected() {
+        if app.dirs.items[selected].0 == "../" {
+            return;
+        }
+        app.dirs.items[selected].0.clone()
+    } else {
+        return;
+    };
+
+    let entry = format!("{}{}", view.prefix, name);
+    let dest = std::env::current_dir().unwrap();
+
+    match extract_entry(&view.path, &entry, &dest, app.extract_conflict) {
+        Ok(report) => app.message = report.summary(&dest.to_string_lossy()),
+        Err(err) => app.message = Some(format!("Failed to extract {}: {}", entry, err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::Write;
+
+    fn entry(path: &str, is_dir: bool) -> ArchiveEntry {
+        ArchiveEntry {
+            path: path.to_string(),
+            size: 0,
+            is_dir,
+        }
+    }
+
+    fn paths(entries: &[ArchiveEntry]) -> Vec<(&str, bool)> {
+        entries
+            .iter()
+            .map(|e| (e.path.as_str(), e.is_dir))
+            .collect()
+    }
+
+    #[test]
+    fn lists_tar_entries_with_their_implied_parents() {
+        let path =
+            std::env::temp_dir().join(format!("traverse-{}-list.tar.gz", std::process::id()));
+
+        let gz = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        for name in ["./src/main.rs", "README.md", "src/ui/mod.rs"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(1);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, &b"a"[..]).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let entries = list_entries(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            paths(&entries),
+            vec![
+                ("README.md", false),
+                ("src", true),
+                ("src/main.rs", false),
+                ("src/ui", true),
+                ("src/ui/mod.rs", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_zip_directories_without_their_slash() {
+        let path = std::env::temp_dir().join(format!("traverse-{}-list.zip", std::process::id()));
+
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        zip.add_directory("docs/", Default::default()).unwrap();
+        zip.start_file("docs/guide.md", Default::default()).unwrap();
+        zip.write_all(b"guide").unwrap();
+        zip.finish().unwrap();
+        drop(zip);
+
+        let entries = list_entries(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            paths(&entries),
+            vec![("docs", true), ("docs/guide.md", false)]
+        );
+        assert_eq!(entries[1].size, 5);
+    }
+
+    #[test]
+    fn children_are_the_entries_directly_under_the_prefix() {
+        let mut view = ArchiveView {
+            path: PathBuf::from("archive.tar"),
+            prefix: String::new(),
+            entries: vec![
+                entry("b.txt", false),
+                entry("a.txt", false),
+                entry("src", true),
+                entry("src/main.rs", false),
+                entry("src/ui", true),
+                entry("src/ui/mod.rs", false),
+            ],
+        };
+
+        assert_eq!(view.files(), vec!["a.txt", "b.txt"]);
+        assert_eq!(view.dirs(), vec!["src"]);
+
+        view.prefix = "src/".to_string();
+        assert_eq!(view.files(), vec!["main.rs"]);
+        assert_eq!(view.dirs(), vec!["ui"]);
+        assert_eq!(view.display_path(), "archive.tar/src/");
+        assert!(matches!(view.entry("ui"), Some(entry) if entry.is_dir));
+    }
+
+    #[test]
+    fn extracts_an_entry_with_the_same_checks_as_the_whole_archive() {
+        let dir = std::env::temp_dir().join(format!("traverse-{}-entry", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let out = dir.join("out");
+        fs::create_dir_all(out.join("ui")).unwrap();
+        fs::write(out.join("ui/mod.rs"), "kept").unwrap();
+
+        let path = dir.join("src.tar.gz");
+        let gz = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        for name in ["src/main.rs", "src/ui/mod.rs", "src/ui/app.rs", "README.md"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(1);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, &b"a"[..]).unwrap();
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "src/ui/up", "../../..")
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let report = extract_entry(&path, "src/ui", &out, Conflict::Skip).unwrap();
+
+        assert_eq!(report.extracted, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.refused, 1);
+        assert_eq!(fs::read_to_string(out.join("ui/mod.rs")).unwrap(), "kept");
+        assert!(out.join("ui/app.rs").exists());
+        assert!(!out.join("main.rs").exists() && !out.join("README.md").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+use super::line_editor::LineEditor;
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::app::job::{Job, ProgressReader};
+use crate::ui::display::block::block_binds;
+use flate2::write::GzEncoder;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use walkdir::WalkDir;
+use xz2::write::XzEncoder;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn from_name(name: &str) -> Option<ArchiveFormat> {
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Some(ArchiveFormat::TarXz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn handle_create_archive(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
+    if block_binds(app) || app.archive.is_some() {
+        return;
+    }
+
+    let name = if app.selected_files.len() > 1 {
+        "archive".to_string()
+    } else if let Some(file) = app.selected_files.first() {
+        Path::new(file)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    } else if let Some(selected) = app.files.state.selected() {
+        match app.files.items.get(selected) {
+            Some(file) => file.0.clone(),
+            None => return,
+        }
+    } else if let Some(selected) = app.dirs.state.selected() {
+        if app.dirs.items[selected].0 == "../" {
+            return;
+        }
+        app.dirs.items[selected].0.clone()
+    } else {
+        return;
+    };
+
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::CreateArchive);
+    input.set(format!("{}.zip", name));
+}
+
+// packs the staged selection, or the highlighted item when nothing is staged,
+// into `name` on a background job
+pub fn create_archive(app: &mut App, name: &str) {
+    let format = match ArchiveFormat::from_name(name) {
+        Some(format) => format,
+        None => {
+            app.message = Some(format!(
+                "Unknown archive format for {}, use .zip, .tar.gz, .tar.xz or .tar.zst",
+                name
+            ));
+            return;
+        }
+    };
+
+    let cur_dir = std::env::current_dir().unwrap();
+    let target = cur_dir.join(name);
+
+    if target.exists() {
+        app.message = Some(format!("{} already exists", name));
+        return;
+    }
+
+    let sources: Vec<PathBuf> = if !app.selected_files.is_empty() {
+        app.selected_files.iter().map(PathBuf::from).collect()
+    } else if let Some(selected) = app.files.state.selected() {
+        vec![cur_dir.join(&app.files.items[selected].0)]
+    } else if let Some(selected) = app.dirs.state.selected() {
+        vec![cur_dir.join(&app.dirs.items[selected].0)]
+    } else {
+        return;
+    };
+
+    app.selected_files = vec![];
+    app.selected_dirs = vec![];
+
+    // walking a large tree takes a while, so the job sizes it up itself
+    let mut job = Job::spawn_sizing(format!("Creating {}", name), move |total, progress| {
+        total.store(total_size(&sources), Ordering::Relaxed);
+
+        let result = write_archive(&target, format, &sources, progress);
+        if result.is_err() {
+            let _ = fs::remove_file(&target);
+        }
+
+        result
+    });
+
+    // the new archive shows up once it's written
+    job.refresh = true;
+    app.jobs.push(job);
+}
+
+fn total_size(sources: &[PathBuf]) -> u64 {
+    sources
+        .iter()
+        .flat_map(|source| WalkDir::new(source).into_iter().flatten())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn write_archive(
+    target: &Path,
+    format: ArchiveFormat,
+    sources: &[PathBuf],
+    progress: Arc<AtomicU64>,
+) -> io::Result<()> {
+    let file = File::create(target)?;
+
+    match format {
+        ArchiveFormat::Zip => write_zip(file, sources, progress),
+        ArchiveFormat::TarGz => {
+            let encoder = GzEncoder::new(file, flate2::Compression::default());
+            write_tar(encoder, sources, progress)?.finish()?;
+            Ok(())
+        }
+        ArchiveFormat::TarXz => {
+            let encoder = XzEncoder::new(file, 6);
+            write_tar(encoder, sources, progress)?.finish()?;
+            Ok(())
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(file, 0)?;
+            write_tar(encoder, sources, progress)?.finish()?;
+            Ok(())
+        }
+    }
+}
+
+// every source is stored under its own name, with anything below it
+// kept relative to that
+fn entries(source: &Path) -> impl Iterator<Item = io::Result<(PathBuf, walkdir::DirEntry)>> + '_ {
+    let base = source.parent().unwrap_or(source).to_path_buf();
+
+    WalkDir::new(source).into_iter().map(move |entry| {
+        let entry = entry.map_err(io::Error::from)?;
+        let name = entry.path().strip_prefix(&base).unwrap().to_path_buf();
+
+        Ok((name, entry))
+    })
+}
+
+fn write_tar<W: Write>(writer: W, sources: &[PathBuf], progress: Arc<AtomicU64>) -> io::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+
+    for source in sources {
+        for entry in entries(source) {
+            let (name, entry) = entry?;
+            let metadata = fs::symlink_metadata(entry.path())?;
+
+            if metadata.is_dir() {
+                builder.append_dir(&name, entry.path())?;
+            } else if metadata.file_type().is_symlink() {
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&metadata);
+                builder.append_link(&mut header, &name, fs::read_link(entry.path())?)?;
+            } else {
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&metadata);
+
+                let reader = ProgressReader {
+                    inner: File::open(entry.path())?,
+                    progress: progress.clone(),
+                };
+                builder.append_data(&mut header, &name, reader)?;
+            }
+        }
+    }
+
+    builder.into_inner()
+}
+
+fn write_zip(file: File, sources: &[PathBuf], progress: Arc<AtomicU64>) -> io::Result<()> {
+    let mut zip = ZipWriter::new(file);
+
+    for source in sources {
+        for entry in entries(source) {
+            let (name, entry) = entry?;
+            let metadata = fs::symlink_metadata(entry.path())?;
+            let name = name.to_string_lossy().to_string();
+
+            let options = FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .unix_permissions(mode(&metadata));
+
+            if metadata.is_dir() {
+                zip.add_directory(name, options)?;
+            } else if metadata.file_type().is_symlink() {
+                let link = fs::read_link(entry.path())?;
+                zip.add_symlink(name, link.to_string_lossy(), options)?;
+            } else {
+                zip.start_file(name, options)?;
+
+                let mut reader = ProgressReader {
+                    inner: File::open(entry.path())?,
+                    progress: progress.clone(),
+                };
+                io::copy(&mut reader, &mut zip)?;
+            }
+        }
+    }
+
+    zip.finish()?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode()
+}
+
+#[cfg(not(unix))]
+fn mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.is_dir() {
+        0o755
+    } else {
+        0o644
+    }
+}
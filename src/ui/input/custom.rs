@@ -0,0 +1,236 @@
+use super::run_app::Command;
+use super::shell::{run_in_output, script, shell};
+use super::stateful_list::StatefulList;
+use crate::app::app::App;
+use crate::app::job::Job;
+use crate::plugin::plugin;
+use crate::ui::display::block::block_binds;
+use std::io;
+use std::process::Stdio;
+
+const FLAGS: [&str; 4] = ["confirm", "output", "terminal", "refresh"];
+
+// a rule from `command=<key>|<name>|<command>|<flags>` in the config, the
+// key may be left empty to only list it in the menu, the flags are any of
+// confirm, output, terminal and refresh separated by commas
+#[derive(Clone)]
+pub struct CustomCommand {
+    pub key: Option<char>,
+    pub name: String,
+    pub template: String,
+    pub confirm: bool,
+    pub output: bool,
+    pub terminal: bool,
+    pub refresh: bool,
+    // `template` is the function of a plugin action
+    pub plugin: bool,
+}
+
+impl CustomCommand {
+    pub fn parse(value: &str) -> Option<CustomCommand> {
+        let (key, rest) = value.split_once('|')?;
+        let (name, rest) = rest.split_once('|')?;
+
+        let mut chars = key.trim().chars();
+        let key = match (chars.next(), chars.next()) {
+            (None, _) => None,
+            (Some(key), None) => Some(key),
+            _ => return None,
+        };
+
+        // like openers, the command may contain pipes so the flags are only
+        // taken from the end when they're all known ones
+        let (template, flags) = match rest.rsplit_once('|') {
+            Some((template, flags))
+                if flags
+                    .split(',')
+                    .all(|flag| FLAGS.contains(&flag.trim().to_lowercase().as_str())) =>
+            {
+                (template, flags.to_lowercase())
+            }
+            _ => (rest, String::new()),
+        };
+        let flags: Vec<&str> = flags.split(',').map(|flag| flag.trim()).collect();
+
+        let name = name.trim().to_string();
+        let template = template.trim().to_string();
+
+        if name.is_empty() || template.is_empty() {
+            return None;
+        }
+
+        Some(CustomCommand {
+            key,
+            name,
+            template,
+            confirm: flags.contains(&"confirm"),
+            output: flags.contains(&"output"),
+            terminal: flags.contains(&"terminal"),
+            refresh: flags.contains(&"refresh"),
+            plugin: false,
+        })
+    }
+
+    pub fn label(&self) -> String {
+        let template = if self.plugin {
+            "plugin"
+        } else {
+            &self.template
+        };
+
+        match self.key {
+            Some(key) => format!("{}  {}  ({})", key, self.name, template),
+            None => format!("   {}  ({})", self.name, template),
+        }
+    }
+}
+
+pub fn handle_custom_menu(app: &mut App) {
+    if block_binds(app) || app.archive.is_some() || app.custom_commands.is_empty() {
+        return;
+    }
+
+    app.custom_menu = StatefulList::with_items(app.custom_commands.clone());
+    app.custom_menu.state.select(Some(0));
+    app.show_custom_menu = true;
+}
+
+pub fn handle_custom_menu_submit(app: &mut App, input_active: &mut bool) {
+    let command = app
+        .custom_menu
+        .state
+        .selected()
+        .and_then(|selected| app.custom_menu.items.get(selected).cloned());
+
+    app.show_custom_menu = false;
+    app.last_command = None;
+
+    if let Some(command) = command {
+        run_custom(app, command, input_active);
+    }
+}
+
+// keys without a built-in binding, returns whether one was bound
+pub fn handle_custom_key(app: &mut App, key: char, input_active: &mut bool) -> bool {
+    if block_binds(app) || app.archive.is_some() {
+        return false;
+    }
+
+    let command = app
+        .custom_commands
+        .iter()
+        .find(|command| command.key == Some(key))
+        .cloned();
+
+    match command {
+        Some(command) => {
+            run_custom(app, command, input_active);
+            true
+        }
+        None => false,
+    }
+}
+
+fn run_custom(app: &mut App, command: CustomCommand, input_active: &mut bool) {
+    if command.confirm {
+        // asked through the input popup, see `handle_confirm_submit`
+        app.pending_custom = Some(command);
+        app.show_popup = true;
+        app.last_command = Some(Command::ConfirmCustom);
+        *input_active = true;
+        return;
+    }
+
+    execute(app, &command);
+}
+
+pub fn handle_confirm_submit(app: &mut App, input: &str) {
+    if let Some(command) = app.pending_custom.take() {
+        if input.trim().to_lowercase().starts_with('y') {
+            execute(app, &command);
+        }
+    }
+}
+
+// in the terminal or with its output shown it runs right away, otherwise
+// it's left to a background job
+fn execute(app: &mut App, command: &CustomCommand) {
+    if command.plugin {
+        plugin::run_action(app, &command.template);
+        return;
+    }
+
+    let script = script(app, &command.template);
+
+    if command.terminal {
+        app.run_in_terminal = Some(shell(&script));
+        return;
+    }
+
+    if command.output {
+        run_in_output(app, &command.name, shell(&script), command.refresh);
+        return;
+    }
+
+    let mut job = Job::spawn(command.name.clone(), 0, move |_| {
+        let output = shell(&script)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .output()?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = match stderr.lines().last() {
+            Some(line) => line.to_string(),
+            None => output.status.to_string(),
+        };
+
+        Err(io::Error::other(reason))
+    });
+
+    job.refresh = command.refresh;
+    app.jobs.push(job);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_custom_commands() {
+        let command = CustomCommand::parse("e| Edit | $EDITOR {} | terminal, refresh").unwrap();
+        assert_eq!(command.key, Some('e'));
+        assert_eq!(command.name, "Edit");
+        assert_eq!(command.template, "$EDITOR {}");
+        assert!(command.terminal && command.refresh);
+        assert!(!command.confirm && !command.output);
+
+        // no key, only in the menu, and no flags
+        let command = CustomCommand::parse("|Disk usage|du -sh {s}").unwrap();
+        assert_eq!(command.key, None);
+        assert_eq!(command.template, "du -sh {s}");
+        assert!(!command.output);
+    }
+
+    #[test]
+    fn flags_are_only_taken_when_all_are_known() {
+        let command = CustomCommand::parse("c|Count|wc -l {} | sort|OUTPUT,Confirm").unwrap();
+        assert_eq!(command.template, "wc -l {} | sort");
+        assert!(command.output && command.confirm);
+
+        let command = CustomCommand::parse("c|Count|wc -l {} | sort").unwrap();
+        assert_eq!(command.template, "wc -l {} | sort");
+        assert!(!command.output);
+    }
+
+    #[test]
+    fn refuses_incomplete_commands() {
+        assert!(CustomCommand::parse("ab|Name|ls").is_none());
+        assert!(CustomCommand::parse("a| |ls").is_none());
+        assert!(CustomCommand::parse("a|Name| |output").is_none());
+        assert!(CustomCommand::parse("a|Name").is_none());
+    }
+}
@@ -1,16 +1,302 @@
+use super::archive::list_entries;
 use crate::app::app::App;
+use bzip2::read::BzDecoder;
 use flate2::read::GzDecoder;
-use std::io::Read;
-use std::{fs::File, io::Cursor};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::io::{Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
 use tar::Archive;
+use xz2::read::XzDecoder;
+use zip::result::ZipError;
+use zip::ZipArchive;
 
-pub fn extract_tar(app: &mut App, file: &str) -> Result<(), std::io::Error> {
-    let path = std::env::current_dir().unwrap().join(file);
+#[derive(PartialEq, Clone, Copy)]
+pub enum Compression {
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
 
-    let tar_gz = File::open(path)?;
-    let tar = GzDecoder::new(tar_gz);
-    let mut archive = Archive::new(tar);
-    archive.unpack(".")?;
+#[derive(PartialEq, Clone, Copy)]
+pub enum Format {
+    Zip,
+    Tar(Compression),
+    // a single compressed file, not a tarball
+    Compressed(Compression),
+}
+
+// sniffs the format from the magic bytes rather than trusting the extension,
+// compressed streams are peeked into to tell tarballs from single files
+pub fn detect_format(path: &Path) -> io::Result<Option<Format>> {
+    let mut file = File::open(path)?;
+    if !file.metadata()?.is_file() {
+        return Ok(None);
+    }
+
+    let mut magic = [0; 6];
+    let read = file.read(&mut magic)?;
+    let magic = &magic[..read];
+    file.seek(SeekFrom::Start(0))?;
+
+    let compression = if magic.starts_with(b"PK\x03\x04") || magic.starts_with(b"PK\x05\x06") {
+        return Ok(Some(Format::Zip));
+    } else if magic.starts_with(&[0x1f, 0x8b]) {
+        Compression::Gzip
+    } else if magic.starts_with(b"BZh") {
+        Compression::Bzip2
+    } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Compression::Xz
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Compression::Zstd
+    } else {
+        Compression::None
+    };
+
+    if is_tar(decoder(file, compression)?) {
+        Ok(Some(Format::Tar(compression)))
+    } else if compression != Compression::None {
+        Ok(Some(Format::Compressed(compression)))
+    } else {
+        Ok(None)
+    }
+}
+
+pub fn decoder(file: File, compression: Compression) -> io::Result<Box<dyn Read>> {
+    Ok(match compression {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(GzDecoder::new(file)),
+        Compression::Bzip2 => Box::new(BzDecoder::new(file)),
+        Compression::Xz => Box::new(XzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::Decoder::new(file)?),
+    })
+}
+
+// the ustar magic sits at offset 257 of the first header block, v7 and old
+// GNU tars don't have it but their header checksum still adds up
+fn is_tar(reader: impl Read) -> bool {
+    let mut header = vec![];
+    if reader.take(512).read_to_end(&mut header).is_err() || header.len() < 512 {
+        return false;
+    }
+
+    &header[257..262] == b"ustar" || checksum_matches(&header)
+}
+
+// the octal checksum at 148 is the sum of the header's bytes, counting its
+// own eight as spaces
+fn checksum_matches(header: &[u8]) -> bool {
+    let field = String::from_utf8_lossy(&header[148..156]);
+    let expected = match u32::from_str_radix(field.trim_matches([' ', '\0']), 8) {
+        Ok(expected) => expected,
+        Err(_) => return false,
+    };
+
+    let sum: u32 = header
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| if (148..156).contains(&i) { b' ' } else { *byte } as u32)
+        .sum();
+
+    sum == expected
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Conflict {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl Conflict {
+    pub fn from_config(value: &str) -> Conflict {
+        match value.to_lowercase().as_str() {
+            "overwrite" => Conflict::Overwrite,
+            "rename" => Conflict::Rename,
+            _ => Conflict::Skip,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ExtractReport {
+    pub extracted: usize,
+    pub skipped: usize,
+    pub refused: usize,
+}
+
+impl ExtractReport {
+    pub fn summary(&self, dest: &str) -> Option<String> {
+        if self.skipped == 0 && self.refused == 0 {
+            return None;
+        }
+
+        Some(format!(
+            "Extracted {} entries to {}, skipped {} existing, refused {} unsafe",
+            self.extracted, dest, self.skipped, self.refused
+        ))
+    }
+}
+
+// the folder every entry lives under, if there is exactly one
+pub fn top_level_dir(file: &str) -> Option<String> {
+    let entries = list_entries(Path::new(file)).ok()?;
+    let first = entries.first()?.path.split('/').next()?.to_string();
+
+    let single = entries
+        .iter()
+        .all(|e| e.path.split('/').next() == Some(first.as_str()));
+    let is_dir = entries.iter().any(|e| e.path == first && e.is_dir);
+
+    if single && is_dir {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+// an archive with a single top-level folder is extracted into a directory
+// of that name, otherwise into one named after the archive
+pub fn default_destination(file: &str) -> String {
+    if let Some(top) = top_level_dir(file) {
+        return top;
+    }
+
+    [
+        ".tar.gz", ".tar.bz2", ".tar.xz", ".tar.zst", ".tgz", ".tar", ".zip",
+    ]
+    .iter()
+    .find_map(|ext| file.strip_suffix(ext))
+    .map(|name| name.to_string())
+    .unwrap_or_else(|| format!("{}.d", file))
+}
+
+// archive paths are only trusted when they stay below the destination,
+// absolute paths and `..` are refused
+pub fn safe_path(path: &Path) -> Option<PathBuf> {
+    let mut safe = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => safe.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    Some(safe)
+}
+
+pub fn resolve_conflict(target: PathBuf, conflict: Conflict) -> Option<PathBuf> {
+    if fs::symlink_metadata(&target).is_err() {
+        return Some(target);
+    }
+
+    match conflict {
+        Conflict::Skip => None,
+        Conflict::Overwrite => Some(target),
+        Conflict::Rename => {
+            let stem = target.file_stem()?.to_string_lossy().to_string();
+            let ext = target
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default();
+
+            (1..)
+                .map(|i| target.with_file_name(format!("{} ({}){}", stem, i, ext)))
+                .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        }
+    }
+}
+
+// the parent of `target` must still be inside `dest` once symlinks
+// created by earlier entries are resolved
+fn inside(dest: &Path, target: &Path) -> io::Result<bool> {
+    let parent = match target.parent() {
+        Some(parent) => parent,
+        None => return Ok(false),
+    };
+
+    fs::create_dir_all(parent)?;
+    Ok(fs::canonicalize(parent)?.starts_with(fs::canonicalize(dest)?))
+}
+
+// encrypted entries can't be opened without a password
+pub fn zip_password_required(file: &str) -> bool {
+    let mut zip = match File::open(file).map(ZipArchive::new) {
+        Ok(Ok(zip)) => zip,
+        _ => return false,
+    };
+
+    (0..zip.len()).any(|i| {
+        matches!(
+            zip.by_index(i),
+            Err(ZipError::UnsupportedArchive(msg)) if msg == ZipError::PASSWORD_REQUIRED
+        )
+    })
+}
+
+// tries the password on the first encrypted entry, reading it through
+// so a password that only passes the header check is caught by the crc
+pub fn check_zip_password(file: &str, password: &str) -> io::Result<bool> {
+    let mut zip = ZipArchive::new(File::open(file)?)?;
+
+    for i in 0..zip.len() {
+        if zip.by_index(i).is_ok() {
+            continue;
+        }
+
+        return match zip.by_index_decrypt(i, password.as_bytes())? {
+            Ok(mut entry) => Ok(io::copy(&mut entry, &mut io::sink()).is_ok()),
+            Err(_) => Ok(false),
+        };
+    }
+
+    Ok(true)
+}
+
+pub fn extract_archive(
+    app: &mut App,
+    file: &str,
+    dest: &str,
+    password: Option<&str>,
+) -> Result<(), std::io::Error> {
+    let format = match detect_format(Path::new(file))? {
+        Some(format) => format,
+        None => return Ok(()),
+    };
+
+    if let Format::Compressed(compression) = format {
+        return decompress(app, file, compression);
+    }
+
+    let strip = top_level_dir(file).map(PathBuf::from);
+    let dest_path = std::env::current_dir().unwrap().join(dest);
+    fs::create_dir_all(&dest_path)?;
+
+    let report = match format {
+        Format::Zip => extract_zip(
+            file,
+            &dest_path,
+            strip,
+            None,
+            app.extract_conflict,
+            password,
+        )?,
+        Format::Tar(compression) => extract_tar(
+            file,
+            compression,
+            &dest_path,
+            strip,
+            None,
+            app.extract_conflict,
+        )?,
+        Format::Compressed(_) => unreachable!(),
+    };
+
+    app.message = report.summary(dest);
 
     app.update_files();
     app.update_dirs();
@@ -18,19 +304,426 @@ pub fn extract_tar(app: &mut App, file: &str) -> Result<(), std::io::Error> {
     Ok(())
 }
 
-pub fn extract_zip(app: &mut App, file: &str) -> Result<(), std::io::Error> {
-    let target_dir = std::env::current_dir().unwrap();
+// with `only` set just that entry and what's below it is extracted, the
+// rest is passed over rather than refused
+fn wanted(path: &Path, only: Option<&Path>) -> bool {
+    match only {
+        Some(only) => matches!(safe_path(path), Some(path) if path.starts_with(only)),
+        None => true,
+    }
+}
+
+fn relative_target(path: &Path, strip: &Option<PathBuf>) -> Option<PathBuf> {
+    let path = safe_path(path)?;
+
+    match strip {
+        Some(top) => path.strip_prefix(top).ok().map(|p| p.to_path_buf()),
+        None => Some(path),
+    }
+}
+
+pub fn extract_tar(
+    file: &str,
+    compression: Compression,
+    dest: &Path,
+    strip: Option<PathBuf>,
+    only: Option<&Path>,
+    conflict: Conflict,
+) -> Result<ExtractReport, std::io::Error> {
+    let mut report = ExtractReport::default();
+
+    let tar = decoder(File::open(file)?, compression)?;
+    let mut archive = Archive::new(tar);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if !wanted(&path, only) {
+            continue;
+        }
+
+        let relative = match relative_target(&path, &strip) {
+            Some(relative) => relative,
+            None => {
+                report.refused += 1;
+                continue;
+            }
+        };
+
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let kind = entry.header().entry_type();
+
+        // symlinks are relative to where they're created, so they only need
+        // to stay below it
+        if let (false, Some(link)) = (kind.is_hard_link(), entry.link_name()?) {
+            let unsafe_link =
+                link.is_absolute() || link.components().any(|c| c == Component::ParentDir);
+
+            if unsafe_link {
+                report.refused += 1;
+                continue;
+            }
+        }
+
+        let target = dest.join(&relative);
+        if !inside(dest, &target)? {
+            report.refused += 1;
+            continue;
+        }
+
+        if kind.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        // hard links name another entry of the archive, `unpack` would look
+        // for it in the current directory rather than under `dest`
+        if kind.is_hard_link() {
+            let source = match entry
+                .link_name()?
+                .and_then(|link| relative_target(&link, &strip))
+            {
+                Some(source) if !source.as_os_str().is_empty() => dest.join(source),
+                _ => {
+                    report.refused += 1;
+                    continue;
+                }
+            };
+
+            // the entry it names may not have been extracted
+            if fs::symlink_metadata(&source).is_err() || !inside(dest, &source)? {
+                report.refused += 1;
+                continue;
+            }
+
+            match resolve_conflict(target, conflict) {
+                Some(target) => {
+                    if fs::symlink_metadata(&target).is_ok() {
+                        fs::remove_file(&target)?;
+                    }
+                    fs::hard_link(&source, &target)?;
+                    report.extracted += 1;
+                }
+                None => report.skipped += 1,
+            }
+            continue;
+        }
+
+        match resolve_conflict(target, conflict) {
+            Some(target) => {
+                entry.unpack(&target)?;
+                report.extracted += 1;
+            }
+            None => report.skipped += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+pub fn extract_zip(
+    file: &str,
+    dest: &Path,
+    strip: Option<PathBuf>,
+    only: Option<&Path>,
+    conflict: Conflict,
+    password: Option<&str>,
+) -> Result<ExtractReport, std::io::Error> {
+    let mut report = ExtractReport::default();
+    let mut zip = ZipArchive::new(File::open(file)?)?;
+
+    for i in 0..zip.len() {
+        let mut entry = match password {
+            Some(password) => match zip.by_index_decrypt(i, password.as_bytes())? {
+                Ok(entry) => entry,
+                Err(err) => return Err(io::Error::new(io::ErrorKind::PermissionDenied, err)),
+            },
+            None => zip.by_index(i)?,
+        };
+
+        if !wanted(Path::new(entry.name()), only) {
+            continue;
+        }
+
+        let relative = match relative_target(Path::new(entry.name()), &strip) {
+            Some(relative) => relative,
+            None => {
+                report.refused += 1;
+                continue;
+            }
+        };
+
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let target = dest.join(&relative);
+        if !inside(dest, &target)? {
+            report.refused += 1;
+            continue;
+        }
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        match resolve_conflict(target, conflict) {
+            Some(target) => {
+                io::copy(&mut entry, &mut File::create(&target)?)?;
+                set_mode(&target, entry.unix_mode())?;
+                report.extracted += 1;
+            }
+            None => report.skipped += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(unix)]
+fn set_mode(target: &Path, mode: Option<u32>) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    match mode {
+        Some(mode) => fs::set_permissions(target, fs::Permissions::from_mode(mode)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn set_mode(_target: &Path, _mode: Option<u32>) -> io::Result<()> {
+    Ok(())
+}
+
+// `log.gz` becomes `log`, anything without a known suffix gets `.out`
+pub fn decompress(
+    app: &mut App,
+    file: &str,
+    compression: Compression,
+) -> Result<(), std::io::Error> {
+    let target = [".gz", ".bz2", ".xz", ".zst"]
+        .iter()
+        .find_map(|ext| file.strip_suffix(ext))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{}.out", file));
 
-    let mut file = File::open(file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
+    let target = match resolve_conflict(PathBuf::from(&target), app.extract_conflict) {
+        Some(target) => target,
+        None => {
+            app.message = Some(format!("{} already exists, skipped", target));
+            return Ok(());
+        }
+    };
 
-    let reader = Cursor::new(buffer);
+    let mut reader = decoder(File::open(file)?, compression)?;
+    let mut output = File::create(&target)?;
 
-    zip_extract::extract(reader, &target_dir, true).unwrap();
+    // a corrupt stream would leave half a file behind
+    if let Err(err) = io::copy(&mut reader, &mut output) {
+        let _ = fs::remove_file(&target);
+        return Err(err);
+    }
 
     app.update_files();
     app.update_dirs();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use tar::{EntryType, Header};
+
+    // a fresh directory under the system's temp dir
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("traverse-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // names are written as they are, `tar` would refuse the unsafe ones
+    fn header(mut header: Header, name: &str, kind: EntryType, link: &str, size: u64) -> Header {
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.as_old_mut().linkname[..link.len()].copy_from_slice(link.as_bytes());
+        header.set_entry_type(kind);
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_cksum();
+        header
+    }
+
+    // names ending in `/` are directories, `name=>target` hard links
+    fn tarball<W: Write>(writer: W, entries: &[&str]) -> W {
+        let mut builder = tar::Builder::new(writer);
+
+        for entry in entries {
+            let (name, kind, link) = match entry.split_once("=>") {
+                Some((name, link)) => (name, EntryType::Link, link),
+                None if entry.ends_with('/') => (*entry, EntryType::Directory, ""),
+                None => (*entry, EntryType::Regular, ""),
+            };
+            let contents: &[u8] = if kind == EntryType::Regular {
+                name.as_bytes()
+            } else {
+                b""
+            };
+
+            let header = header(Header::new_gnu(), name, kind, link, contents.len() as u64);
+            builder.append(&header, contents).unwrap();
+        }
+
+        builder.into_inner().unwrap()
+    }
+
+    fn write_tar(path: &Path, entries: &[&str]) -> String {
+        tarball(File::create(path).unwrap(), entries);
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn safe_path_stays_below_the_destination() {
+        assert_eq!(safe_path(Path::new("a/./b")), Some(PathBuf::from("a/b")));
+        assert_eq!(safe_path(Path::new("../a")), None);
+        assert_eq!(safe_path(Path::new("a/../../b")), None);
+        assert_eq!(safe_path(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn detects_formats_from_their_contents() {
+        let dir = scratch("detect");
+
+        let tar = dir.join("plain.bin");
+        write_tar(&tar, &["a.txt"]);
+        assert!(detect_format(&tar).unwrap() == Some(Format::Tar(Compression::None)));
+
+        let tgz = dir.join("archive");
+        let encoder = GzEncoder::new(File::create(&tgz).unwrap(), Default::default());
+        tarball(encoder, &["a.txt"]).finish().unwrap();
+        assert!(detect_format(&tgz).unwrap() == Some(Format::Tar(Compression::Gzip)));
+
+        let gz = dir.join("notes.gz");
+        let mut encoder = GzEncoder::new(File::create(&gz).unwrap(), Default::default());
+        encoder.write_all(b"just some text").unwrap();
+        encoder.finish().unwrap();
+        assert!(detect_format(&gz).unwrap() == Some(Format::Compressed(Compression::Gzip)));
+
+        let zip = dir.join("archive.tar");
+        let mut writer = zip::ZipWriter::new(File::create(&zip).unwrap());
+        writer.start_file("a.txt", Default::default()).unwrap();
+        writer.write_all(b"a").unwrap();
+        writer.finish().unwrap();
+        assert!(detect_format(&zip).unwrap() == Some(Format::Zip));
+
+        let text = dir.join("text.tar");
+        fs::write(&text, "not an archive").unwrap();
+        assert!(detect_format(&text).unwrap().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn detects_v7_tars_by_their_checksum() {
+        let dir = scratch("v7");
+        let path = dir.join("old.tar");
+
+        let header = header(Header::new_old(), "a.txt", EntryType::Regular, "", 1);
+        let mut builder = tar::Builder::new(File::create(&path).unwrap());
+        builder.append(&header, &b"a"[..]).unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+
+        assert!(detect_format(&path).unwrap() == Some(Format::Tar(Compression::None)));
+
+        // the same block with a byte changed no longer adds up
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[0] = b'b';
+        fs::write(&path, bytes).unwrap();
+        assert!(detect_format(&path).unwrap().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn finds_a_single_top_level_dir() {
+        let dir = scratch("top");
+
+        let single = write_tar(&dir.join("single.tar"), &["project/", "project/a.txt"]);
+        assert_eq!(top_level_dir(&single), Some("project".to_string()));
+
+        // the directory only implied by its entries' paths
+        let implied = write_tar(&dir.join("implied.tar"), &["project/src/main.rs"]);
+        assert_eq!(top_level_dir(&implied), Some("project".to_string()));
+
+        let loose = write_tar(&dir.join("loose.tar"), &["a.txt", "b.txt"]);
+        assert_eq!(top_level_dir(&loose), None);
+
+        let file = write_tar(&dir.join("file.tar"), &["a.txt"]);
+        assert_eq!(top_level_dir(&file), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tar_entries_and_hard_links_stay_below_the_destination() {
+        let dir = scratch("escape");
+        let dest = dir.join("out");
+        fs::create_dir(&dest).unwrap();
+
+        let file = write_tar(
+            &dir.join("evil.tar"),
+            &[
+                "ok.txt",
+                "../evil.txt",
+                "/tmp/absolute.txt",
+                "link=>ok.txt",
+                "escape=>../outside.txt",
+            ],
+        );
+        fs::write(dir.join("outside.txt"), "outside").unwrap();
+
+        let report =
+            extract_tar(&file, Compression::None, &dest, None, None, Conflict::Skip).unwrap();
+
+        assert_eq!(report.extracted, 2);
+        assert_eq!(report.refused, 3);
+        assert_eq!(fs::read_to_string(dest.join("link")).unwrap(), "ok.txt");
+        assert!(!dest.join("escape").exists());
+        assert!(!dir.join("evil.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn conflicts_are_skipped_overwritten_or_renamed() {
+        let dir = scratch("conflict");
+        let target = dir.join("a.txt");
+
+        assert_eq!(
+            resolve_conflict(target.clone(), Conflict::Skip),
+            Some(target.clone())
+        );
+
+        fs::write(&target, "a").unwrap();
+        assert_eq!(resolve_conflict(target.clone(), Conflict::Skip), None);
+        assert_eq!(
+            resolve_conflict(target.clone(), Conflict::Overwrite),
+            Some(target.clone())
+        );
+        assert_eq!(
+            resolve_conflict(target, Conflict::Rename),
+            Some(dir.join("a (1).txt"))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
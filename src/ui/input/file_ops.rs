@@ -1,7 +1,13 @@
-use super::{extract::*, run_app::Command};
+use super::{archive, extract::*, line_editor::LineEditor, run_app::Command, tabs};
 use crate::{app::app::App, ui::display::block::block_binds};
+use std::path::Path;
 
 pub fn handle_new_file(app: &mut App, input_active: &mut bool) {
+    // archives are browsed read-only
+    if app.archive.is_some() {
+        return;
+    }
+
     if app.files.state.selected().is_some() {
         if (*input_active == false && app.last_command != Some(Command::CreateFile))
             || (*input_active == true && app.last_command.is_none())
@@ -22,6 +28,11 @@ pub fn handle_new_file(app: &mut App, input_active: &mut bool) {
 }
 
 pub fn handle_delete(app: &mut App) {
+    // archives are browsed read-only
+    if app.archive.is_some() {
+        return;
+    }
+
     if let Some(selected) = app.files.state.selected() {
         if selected == 0 && app.files.items.len() == 0 {
             return;
@@ -55,8 +66,8 @@ pub fn handle_delete(app: &mut App) {
     }
 }
 
-pub fn handle_rename(app: &mut App, input: &mut String, input_active: &mut bool) {
-    if block_binds(app) {
+pub fn handle_rename(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
+    if block_binds(app) || app.archive.is_some() {
         return;
     }
 
@@ -66,9 +77,11 @@ pub fn handle_rename(app: &mut App, input: &mut String, input_active: &mut bool)
             app.show_popup = true;
             app.last_command = Some(Command::RenameFile);
 
-            *input = app.files.items[app.files.state.selected().unwrap()]
-                .0
-                .clone();
+            input.set(
+                app.files.items[app.files.state.selected().unwrap()]
+                    .0
+                    .clone(),
+            );
         }
     } else if app.dirs.state.selected().is_some() {
         if app.dirs.items[app.dirs.state.selected().unwrap()].0 == "../" {
@@ -78,22 +91,41 @@ pub fn handle_rename(app: &mut App, input: &mut String, input_active: &mut bool)
                 *input_active = true;
                 app.show_popup = true;
                 app.last_command = Some(Command::RenameDir);
-                *input = app.dirs.items[app.dirs.state.selected().unwrap()].0.clone();
+                input.set(app.dirs.items[app.dirs.state.selected().unwrap()].0.clone());
             }
         }
     }
 }
 
-pub fn extract(app: &mut App) {
-    if app.files.state.selected().is_some() {
-        let file = app.files.items[app.files.state.selected().unwrap()]
-            .0
-            .clone();
-
-        if file.ends_with(".tar.gz") {
-            extract_tar(app, &file).expect("Failed to extract tar file");
-        } else if file.ends_with(".zip") {
-            extract_zip(app, &file).expect("Failed to extract zip file");
+pub fn extract(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    if app.archive.is_some() {
+        archive::extract_selected(app);
+        return;
+    }
+
+    if let Some(selected) = app.files.state.selected() {
+        let file = match app.files.items.get(selected) {
+            Some(file) => file.0.clone(),
+            None => return,
+        };
+
+        match detect_format(Path::new(&file)) {
+            Ok(Some(Format::Compressed(_))) => {
+                if let Err(err) = extract_archive(app, &file, ".", None) {
+                    app.message = Some(format!("Failed to extract {}: {}", file, err));
+                }
+            }
+            Ok(Some(_)) => {
+                *input_active = true;
+                app.show_popup = true;
+                app.last_command = Some(Command::ExtractArchive);
+                input.set(default_destination(&file));
+            }
+            _ => {}
         }
     }
 }
@@ -124,6 +156,11 @@ fn add_file(app: &mut App) {
 }
 
 pub fn add_to_selected(app: &mut App) {
+    // archives are browsed read-only
+    if app.archive.is_some() {
+        return;
+    }
+
     if app.dirs.state.selected().is_some() {
         add_dir(app);
     } else if app.files.state.selected().is_some() {
@@ -135,6 +172,12 @@ pub fn handle_paste_or_move(app: &mut App) {
     // TODO:
     // copying files into directories where they already exist
     // (error box maybe for global error handling)
+    if let Some(selected) = app.ops_menu.state.selected() {
+        if tabs::handle_tab_operation(app, selected) {
+            return;
+        }
+    }
+
     if app.selected_files.len() == 0 {
         if app.selected_dirs.len() == 0 {
             return;
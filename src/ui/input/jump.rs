@@ -0,0 +1,291 @@
+use super::line_editor::LineEditor;
+use super::run_app::Command;
+use super::stateful_list::StatefulList;
+use crate::app::app::App;
+use crate::app::history::Position;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::path::{Path, PathBuf};
+
+// call before changing directory, so it can be gone back to once `arrive`
+// is called. If the change fails, `stay` forgets it
+pub fn leave(app: &mut App) {
+    if let Ok(cwd) = std::env::current_dir() {
+        save_position(app, &cwd);
+        app.history.leaving = Some(cwd);
+    }
+}
+
+pub fn stay(app: &mut App) {
+    app.history.leaving = None;
+}
+
+// call after changing directory and updating the listings, returns
+// whether the cursor was put back where it was left
+pub fn arrive(app: &mut App) -> bool {
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return false,
+    };
+
+    // `leave` doesn't know where it's going, so staying put isn't history
+    if let Some(left) = app.history.leaving.take() {
+        if left != cwd {
+            app.history.back.push(left);
+            app.history.forward.clear();
+        }
+    }
+
+    app.history.visit(cwd.clone());
+    app.frecency.visit(&cwd);
+    restore_position(app, &cwd)
+}
+
+pub fn capture_position(app: &App) -> Position {
+    let name = |list: &StatefulList<(String, String)>| {
+        list.state
+            .selected()
+            .and_then(|selected| list.items.get(selected))
+            .map(|item| item.0.clone())
+    };
+
+    Position {
+        files: app.files.state.clone(),
+        dirs: app.dirs.state.clone(),
+        file: name(&app.files),
+        dir: name(&app.dirs),
+    }
+}
+
+// the listings have to be up to date first
+pub fn apply_position(app: &mut App, position: &Position) {
+    restore_list(&mut app.files, &position.files, &position.file);
+    restore_list(&mut app.dirs, &position.dirs, &position.dir);
+
+    if app.files.state.selected().is_none() && app.dirs.state.selected().is_none() {
+        app.dirs.state.select(Some(0));
+    }
+}
+
+fn save_position(app: &mut App, dir: &Path) {
+    let position = capture_position(app);
+    app.history.positions.insert(dir.to_path_buf(), position);
+}
+
+fn restore_position(app: &mut App, dir: &Path) -> bool {
+    match app.history.positions.get(dir).cloned() {
+        Some(position) => {
+            apply_position(app, &position);
+            true
+        }
+        None => false,
+    }
+}
+
+// the saved state keeps the scroll offset, but only fits if the entry is
+// still at the same index
+pub fn restore_list(
+    list: &mut StatefulList<(String, String)>,
+    state: &ratatui::widgets::ListState,
+    name: &Option<String>,
+) {
+    let name = match name {
+        Some(name) => name,
+        None => {
+            list.state.select(None);
+            return;
+        }
+    };
+
+    match list.items.iter().position(|item| item.0 == *name) {
+        Some(i) if state.selected() == Some(i) => list.state = state.clone(),
+        Some(i) => list.state.select(Some(i)),
+        None if !list.items.is_empty() => {
+            let i = state.selected().unwrap_or(0).min(list.items.len() - 1);
+            list.state.select(Some(i));
+        }
+        None => list.state.select(None),
+    }
+}
+
+fn go(app: &mut App, dir: &Path) -> bool {
+    if let Err(err) = std::env::set_current_dir(dir) {
+        app.message = Some(format!("Cannot open {}: {}", dir.display(), err));
+        return false;
+    }
+
+    app.archive = None;
+    app.cur_dir = get_pwd();
+    app.update_files();
+    app.update_dirs();
+
+    if !arrive(app) {
+        app.files.state.select(Some(0));
+        app.dirs.state.select(None);
+    }
+
+    true
+}
+
+pub fn handle_back(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return,
+    };
+
+    // directories that are gone are skipped
+    while let Some(dir) = app.history.back.pop() {
+        save_position(app, &cwd);
+
+        if go(app, &dir) {
+            app.history.forward.push(cwd);
+            return;
+        }
+    }
+}
+
+pub fn handle_forward(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return,
+    };
+
+    while let Some(dir) = app.history.forward.pop() {
+        save_position(app, &cwd);
+
+        if go(app, &dir) {
+            app.history.back.push(cwd);
+            return;
+        }
+    }
+}
+
+pub fn handle_recent(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let recent: Vec<String> = app
+        .history
+        .recent
+        .iter()
+        .filter(|dir| **dir != cwd)
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect();
+
+    if recent.is_empty() {
+        return;
+    }
+
+    app.recent = StatefulList::with_items(recent);
+    app.recent.state.select(Some(0));
+    app.show_recent = true;
+}
+
+pub fn handle_open_recent(app: &mut App) {
+    let dir = app
+        .recent
+        .state
+        .selected()
+        .and_then(|selected| app.recent.items.get(selected))
+        .map(PathBuf::from);
+
+    app.show_recent = false;
+    app.last_command = None;
+
+    if let Some(dir) = dir {
+        leave(app);
+
+        if !go(app, &dir) {
+            stay(app);
+        }
+    }
+}
+
+pub fn handle_jump(app: &mut App, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    // each database is only imported once, it's noted in ours
+    for source in app.frecency_import.clone() {
+        if app.frecency.imported.contains(&source) || app.frecency.failed.contains(&source) {
+            continue;
+        }
+
+        match app.frecency.import(&source) {
+            Ok(count) => {
+                app.message = Some(format!("Imported {} directories from {}", count, source));
+            }
+            Err(err) => app.message = Some(err),
+        }
+    }
+
+    app.jump_query = None;
+    app.show_jump = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::Jump);
+    *input_active = true;
+
+    update_jump(app, "");
+}
+
+// called after every key while the prompt is open, only ranks again when
+// the input changed
+pub fn update_jump(app: &mut App, input: &str) {
+    if app.jump_query.as_deref() == Some(input) {
+        return;
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let results: Vec<String> = app
+        .frecency
+        .query(input, &cwd)
+        .iter()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect();
+
+    app.jump_results = StatefulList::with_items(results);
+    if !app.jump_results.items.is_empty() {
+        app.jump_results.state.select(Some(0));
+    }
+
+    app.jump_query = Some(input.to_string());
+}
+
+pub fn handle_open_jump(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
+    let dir = app
+        .jump_results
+        .state
+        .selected()
+        .and_then(|selected| app.jump_results.items.get(selected))
+        .map(PathBuf::from);
+
+    if dir.is_none() {
+        return;
+    }
+
+    app.show_jump = false;
+    app.show_popup = false;
+    app.last_command = None;
+    app.jump_query = None;
+    input.clear();
+    *input_active = false;
+
+    if let Some(dir) = dir {
+        leave(app);
+
+        if !go(app, &dir) {
+            stay(app);
+        }
+    }
+}
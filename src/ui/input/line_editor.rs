@@ -0,0 +1,340 @@
+use super::run_app::Command;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+const HISTORY_LIMIT: usize = 100;
+
+// the text of whichever prompt is open, with a cursor and the previous
+// inputs of each kind of prompt
+#[derive(Default)]
+pub struct LineEditor {
+    text: String,
+    // a byte offset, always on a char boundary
+    cursor: usize,
+    histories: HashMap<Command, Vec<String>>,
+    // the history entry being shown and what was typed before going to it
+    browsing: Option<(usize, String)>,
+}
+
+impl Deref for LineEditor {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+// words end at whitespace or a path separator, so Ctrl+w takes off one
+// directory at a time
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == '/'
+}
+
+impl LineEditor {
+    // replaces the text, with the cursor at the end
+    pub fn set(&mut self, text: String) {
+        self.text = text;
+        self.cursor = self.text.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+        self.browsing = None;
+    }
+
+    // the cursor as a count of chars, for drawing it
+    pub fn cursor_column(&self) -> usize {
+        self.text[..self.cursor].chars().count()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    // pasted text, prompts are a single line so line breaks become spaces
+    pub fn insert_str(&mut self, text: &str) {
+        let text: String = text
+            .trim_end_matches(['\r', '\n'])
+            .chars()
+            .map(|c| if c == '\r' || c == '\n' { ' ' } else { c })
+            .filter(|c| !c.is_control())
+            .collect();
+
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map(|c| self.cursor + c.len_utf8())
+            .unwrap_or(self.cursor)
+    }
+
+    fn word_start(&self) -> usize {
+        let before = &self.text[..self.cursor];
+        let trimmed = before.trim_end_matches(is_separator);
+
+        match trimmed.rfind(is_separator) {
+            Some(i) => i + trimmed[i..].chars().next().unwrap().len_utf8(),
+            None => 0,
+        }
+    }
+
+    fn word_end(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let skipped = after.len() - after.trim_start_matches(is_separator).len();
+
+        match after[skipped..].find(is_separator) {
+            Some(i) => self.cursor + skipped + i,
+            None => self.text.len(),
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.previous_boundary();
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.previous_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    pub fn delete_word(&mut self) {
+        let start = self.word_start();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    pub fn delete_to_end(&mut self) {
+        self.text.truncate(self.cursor);
+    }
+
+    // editing keys, returns whether the key was one
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Char(c) if !control && !alt => self.insert(c),
+            KeyCode::Char('a') if control => self.cursor = 0,
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::Char('e') if control => self.cursor = self.text.len(),
+            KeyCode::End => self.cursor = self.text.len(),
+            KeyCode::Char('b') if control => self.left(),
+            KeyCode::Char('f') if control => self.right(),
+            KeyCode::Char('b') if alt => self.cursor = self.word_start(),
+            KeyCode::Char('f') if alt => self.cursor = self.word_end(),
+            KeyCode::Left if control || alt => self.cursor = self.word_start(),
+            KeyCode::Right if control || alt => self.cursor = self.word_end(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Char('w') if control => self.delete_word(),
+            KeyCode::Backspace if control || alt => self.delete_word(),
+            KeyCode::Char('u') if control => self.delete_to_start(),
+            KeyCode::Char('k') if control => self.delete_to_end(),
+            KeyCode::Char('h') if control => self.backspace(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Char('d') if control => self.delete(),
+            KeyCode::Delete => self.delete(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn history(&self, prompt: Command) -> &[String] {
+        self.histories
+            .get(&prompt)
+            .map(|history| history.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn set_history(&mut self, prompt: Command, history: Vec<String>) {
+        self.histories.insert(prompt, history);
+    }
+
+    // keeps what was submitted, passwords and confirmations are left out
+    pub fn remember(&mut self, prompt: Command) {
+        self.browsing = None;
+
+        if self.text.trim().is_empty()
+            || prompt == Command::ZipPassword
+            || prompt == Command::ConfirmCustom
+        {
+            return;
+        }
+
+        let history = self.histories.entry(prompt).or_default();
+        history.retain(|previous| *previous != self.text);
+        history.push(self.text.clone());
+
+        if history.len() > HISTORY_LIMIT {
+            history.remove(0);
+        }
+    }
+
+    // Up in a prompt, newest first
+    pub fn history_previous(&mut self, prompt: Command) {
+        let len = self.history(prompt).len();
+        if len == 0 {
+            return;
+        }
+
+        let (index, draft) = match self.browsing.take() {
+            Some((index, draft)) => (index.saturating_sub(1), draft),
+            None => (len - 1, self.text.clone()),
+        };
+
+        let text = self.history(prompt)[index].clone();
+        self.browsing = Some((index, draft));
+        self.set(text);
+    }
+
+    // Down, past the newest entry it's back to what was being typed
+    pub fn history_next(&mut self, prompt: Command) {
+        let (index, draft) = match self.browsing.take() {
+            Some(browsing) => browsing,
+            None => return,
+        };
+
+        match self.history(prompt).get(index + 1).cloned() {
+            Some(text) => {
+                self.browsing = Some((index + 1, draft));
+                self.set(text);
+            }
+            None => self.set(draft),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.set(text.to_string());
+        editor
+    }
+
+    #[test]
+    fn deletes_words_a_path_segment_at_a_time() {
+        let mut editor = editor("cd ~/projects/traverse/");
+
+        editor.delete_word();
+        assert_eq!(&*editor, "cd ~/projects/");
+        editor.delete_word();
+        assert_eq!(&*editor, "cd ~/");
+        editor.delete_word();
+        assert_eq!(&*editor, "cd ");
+        editor.delete_word();
+        assert_eq!(&*editor, "");
+        editor.delete_word();
+        assert_eq!(&*editor, "");
+    }
+
+    #[test]
+    fn moves_and_edits_by_chars_not_bytes() {
+        let mut editor = editor("añ🦀");
+
+        editor.left();
+        assert_eq!(editor.cursor_column(), 2);
+        editor.backspace();
+        assert_eq!(&*editor, "a🦀");
+        editor.insert('é');
+        assert_eq!(&*editor, "aé🦀");
+        assert_eq!(editor.cursor_column(), 2);
+
+        editor.right();
+        editor.right();
+        assert_eq!(editor.cursor_column(), 3);
+
+        editor.handle_key(KeyEvent::new(KeyCode::Home, KeyModifiers::NONE));
+        editor.delete();
+        assert_eq!(&*editor, "é🦀");
+        editor.delete_to_end();
+        assert_eq!(&*editor, "");
+    }
+
+    #[test]
+    fn words_around_the_cursor() {
+        let mut editor = editor("mv ünïcode/ files");
+        let word_left = KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL);
+        let word_right = KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL);
+
+        editor.handle_key(word_left);
+        assert_eq!(editor.cursor_column(), 12);
+        editor.handle_key(word_left);
+        assert_eq!(editor.cursor_column(), 3);
+        editor.handle_key(word_right);
+        assert_eq!(editor.cursor_column(), 10);
+
+        editor.delete_to_start();
+        assert_eq!(&*editor, "/ files");
+    }
+
+    #[test]
+    fn pastes_on_one_line() {
+        let mut editor = editor("");
+        editor.insert_str("one\ntwo\u{7}\r\n");
+        assert_eq!(&*editor, "one two");
+        assert_eq!(editor.cursor_column(), 7);
+    }
+
+    #[test]
+    fn browses_history_newest_first_and_back_to_the_draft() {
+        let mut editor = editor("ls");
+        editor.remember(Command::RunShell);
+        editor.set("make".to_string());
+        editor.remember(Command::RunShell);
+        // submitted again, it moves to the end rather than being repeated
+        editor.set("ls".to_string());
+        editor.remember(Command::RunShell);
+        editor.set("secret".to_string());
+        editor.remember(Command::ZipPassword);
+
+        assert_eq!(editor.history(Command::RunShell), ["make", "ls"]);
+        assert!(editor.history(Command::ZipPassword).is_empty());
+
+        editor.set("draft".to_string());
+        editor.history_previous(Command::RunShell);
+        assert_eq!(&*editor, "ls");
+        editor.history_previous(Command::RunShell);
+        assert_eq!(&*editor, "make");
+        editor.history_previous(Command::RunShell);
+        assert_eq!(&*editor, "make");
+
+        editor.history_next(Command::RunShell);
+        assert_eq!(&*editor, "ls");
+        editor.history_next(Command::RunShell);
+        assert_eq!(&*editor, "draft");
+    }
+}
@@ -0,0 +1,177 @@
+use super::line_editor::LineEditor;
+use super::submit;
+use crate::app::app::{sort_listing, App};
+use crate::app::layout::PaneLayout;
+use crate::app::tab::Sort;
+use crate::filetype::cache::FileCache;
+use crate::ui::display::block::block_binds;
+use std::path::Path;
+
+// the parent column and directory previews, by directory, order and
+// whether hidden entries are shown
+static LISTINGS: FileCache<(Sort, bool), Vec<(String, bool)>> = FileCache::new();
+
+pub fn miller(app: &App) -> bool {
+    app.layout == PaneLayout::Miller
+}
+
+// names with whether they're directories, in one list
+fn merge(
+    app: &mut App,
+    mut entries: Vec<(String, String)>,
+    dirs: &[String],
+    dir: &Path,
+) -> Vec<(String, bool)> {
+    // archive entries aren't on disk to be sorted by size or date
+    let sort = if app.archive.is_some() && dir == Path::new("./") {
+        Sort::Name
+    } else {
+        app.sort
+    };
+
+    sort_listing(dir, sort, &mut entries);
+    app.plugin_sort(&mut entries);
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let is_dir = dirs.contains(&entry.0);
+            (entry.0, is_dir)
+        })
+        .collect()
+}
+
+// the current directory's listings merged and sorted together, `../` is
+// left to the parent column. Kept until the listings are read again
+pub fn entries(app: &mut App) -> Vec<(String, bool)> {
+    if let Some(merged) = &app.merged {
+        return merged.clone();
+    }
+
+    let dirs: Vec<String> = app
+        .dirs
+        .items
+        .iter()
+        .map(|item| item.0.clone())
+        .filter(|name| name != "../")
+        .collect();
+
+    let entries = app
+        .files
+        .items
+        .iter()
+        .cloned()
+        .chain(dirs.iter().map(|name| (name.clone(), name.clone())))
+        .collect();
+
+    let merged = merge(app, entries, &dirs, Path::new("./"));
+    app.merged = Some(merged.clone());
+    merged
+}
+
+// another directory's entries, for the parent column and previewing
+// directories
+pub fn listing(app: &mut App, dir: &Path) -> Vec<(String, bool)> {
+    let sort = app.sort;
+
+    LISTINGS.get_or_insert_with(dir, (sort, app.show_hidden), || {
+        let files = app.list_entries(dir, false, "", sort);
+        let dirs = app.list_entries(dir, true, "", sort);
+        let names: Vec<String> = dirs.iter().map(|dir| dir.0.clone()).collect();
+
+        merge(app, files.into_iter().chain(dirs).collect(), &names, dir)
+    })
+}
+
+// the highlighted entry, from whichever list it's in
+pub fn selected(app: &App) -> Option<String> {
+    let item = if let Some(selected) = app.files.state.selected() {
+        app.files.items.get(selected)
+    } else {
+        app.dirs.items.get(app.dirs.state.selected()?)
+    };
+
+    item.map(|item| item.0.clone())
+}
+
+// highlights `entries[index]` in the list it came from, so everything else
+// keeps working on `files` and `dirs`
+pub fn select(app: &mut App, entries: &[(String, bool)], index: usize) {
+    let (name, is_dir) = match entries.get(index) {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    if *is_dir {
+        let i = app.dirs.items.iter().position(|item| item.0 == *name);
+        app.dirs.state.select(i);
+        app.files.state.select(None);
+    } else {
+        let i = app.files.items.iter().position(|item| item.0 == *name);
+        app.files.state.select(i);
+        app.dirs.state.select(None);
+    }
+}
+
+// the index of the highlighted entry, moving the cursor onto the first
+// entry when it's on `../` or nothing
+pub fn normalize(app: &mut App, entries: &[(String, bool)]) -> Option<usize> {
+    let index = selected(app).and_then(|name| entries.iter().position(|entry| entry.0 == name));
+
+    match index {
+        Some(index) => Some(index),
+        None if !entries.is_empty() => {
+            select(app, entries, 0);
+            Some(0)
+        }
+        None => None,
+    }
+}
+
+// Left, the directory that was left stays highlighted in its parent
+pub fn handle_parent(app: &mut App, input: &mut LineEditor) {
+    if block_binds(app) {
+        return;
+    }
+
+    // leaving an archive's top directory goes back to the archive itself
+    let child = match &app.archive {
+        Some(archive) if !archive.prefix.is_empty() => archive
+            .prefix
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .map(str::to_string),
+        Some(archive) => archive
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+        None => std::env::current_dir().ok().and_then(|cwd| {
+            cwd.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        }),
+    };
+
+    app.files.state.select(None);
+    app.dirs.state.select(Some(0));
+    submit::handle_submit(app, input, &mut false);
+
+    if let Some(child) = child {
+        let entries = entries(app);
+        if let Some(index) = entries.iter().position(|entry| entry.0 == child) {
+            select(app, &entries, index);
+        }
+    }
+}
+
+// Right, into the highlighted directory or opening the highlighted file
+pub fn handle_enter(app: &mut App, input: &mut LineEditor) {
+    if block_binds(app) {
+        return;
+    }
+
+    let entries = entries(app);
+    if normalize(app, &entries).is_some() {
+        submit::handle_submit(app, input, &mut false);
+    }
+}
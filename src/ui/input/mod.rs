@@ -1,9 +1,21 @@
+pub mod archive;
 pub mod bookmark;
+pub mod compress;
+pub mod custom;
 pub mod extract;
 pub mod file_ops;
 pub mod help;
+pub mod jump;
+pub mod line_editor;
+pub mod miller;
+pub mod mouse;
 pub mod movement;
 pub mod nav;
+pub mod open;
+pub mod panes;
 pub mod run_app;
+pub mod shell;
 pub mod stateful_list;
 pub mod submit;
+pub mod tabs;
+pub mod tree;
@@ -0,0 +1,287 @@
+use super::movement::Motion;
+use super::{miller, movement, panes, tree};
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::widgets::ListState;
+use std::time::{Duration, Instant};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(500);
+
+// lines the preview moves per step of the wheel
+const PREVIEW_SCROLL: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Region {
+    Files,
+    Dirs,
+    // the inactive side of the dual layout
+    OtherFiles,
+    OtherDirs,
+    // the miller layout's parent column
+    Parent,
+    // the miller layout's current column, or the tree
+    Current,
+    Preview,
+    // whichever popup list is open
+    Popup,
+    Output,
+}
+
+// where things were drawn in the last frame, with the scroll offset of
+// lists. ratatui 0.20 keeps that to itself, so it's worked out again here
+// from where each list was scrolled to before
+#[derive(Default)]
+pub struct Areas {
+    regions: Vec<(Region, Rect, usize)>,
+    offsets: Vec<(Region, usize)>,
+}
+
+// how ratatui 0.20 scrolls a list of one line entries: as little as it takes
+// to keep the selected one in view
+fn offset(previous: usize, selected: Option<usize>, len: usize, height: usize) -> usize {
+    if len == 0 || height == 0 {
+        return previous;
+    }
+
+    let offset = previous.min(len - 1);
+    let selected = selected.unwrap_or(0).min(len - 1);
+
+    if selected >= offset + height {
+        selected + 1 - height
+    } else {
+        offset.min(selected)
+    }
+}
+
+impl Areas {
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    // a bordered list of `len` entries, as it was rendered this frame
+    pub fn add_list(&mut self, region: Region, area: Rect, state: &ListState, len: usize) {
+        let previous = self
+            .offsets
+            .iter()
+            .find(|(scrolled, _)| *scrolled == region)
+            .map_or(0, |(_, offset)| *offset);
+
+        self.push_list(region, area, state, len, previous);
+    }
+
+    // a list whose state was made for this frame, so it scrolled from the top
+    pub fn add_new_list(&mut self, region: Region, area: Rect, state: &ListState, len: usize) {
+        self.push_list(region, area, state, len, 0);
+    }
+
+    fn push_list(
+        &mut self,
+        region: Region,
+        area: Rect,
+        state: &ListState,
+        len: usize,
+        previous: usize,
+    ) {
+        let height = (area.height as usize).saturating_sub(2);
+        let offset = offset(previous, state.selected(), len, height);
+
+        self.offsets.retain(|(scrolled, _)| *scrolled != region);
+        self.offsets.push((region, offset));
+        self.regions.push((region, area, offset));
+    }
+
+    pub fn add(&mut self, region: Region, area: Rect) {
+        self.regions.push((region, area, 0));
+    }
+
+    // where a list was drawn, and how far it was scrolled
+    pub fn list(&self, region: Region) -> Option<(Rect, usize)> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(drawn, _, _)| *drawn == region)
+            .map(|(_, area, offset)| (*area, *offset))
+    }
+
+    // what's at a cell, with the index of the list entry on that row. The
+    // last thing drawn there is the one on top
+    fn at(&self, x: u16, y: u16) -> Option<(Region, Option<usize>)> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, area, _)| {
+                x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+            })
+            .map(|(region, area, offset)| {
+                let inside = y > area.y && y + 1 < area.y + area.height;
+                let index = inside.then(|| (y - area.y - 1) as usize + offset);
+                (*region, index)
+            })
+    }
+}
+
+// the open popup's list, as its state and length
+fn popup(app: &mut App) -> Option<(&mut ListState, usize)> {
+    if app.show_fzf {
+        Some((&mut app.fzf_results.state, app.fzf_results.items.len()))
+    } else if app.show_jump {
+        Some((&mut app.jump_results.state, app.jump_results.items.len()))
+    } else if app.show_bookmark {
+        Some((
+            &mut app.bookmarked_dirs.state,
+            app.bookmarked_dirs.items.len(),
+        ))
+    } else if app.show_ops_menu {
+        Some((&mut app.ops_menu.state, app.ops_menu.items.len()))
+    } else if app.show_open_with {
+        Some((&mut app.open_with.state, app.open_with.items.len()))
+    } else if app.show_custom_menu {
+        Some((&mut app.custom_menu.state, app.custom_menu.items.len()))
+    } else if app.show_recent {
+        Some((&mut app.recent.state, app.recent.items.len()))
+    } else {
+        None
+    }
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+// returns a key for `run_app` to handle as if it was pressed, for double
+// clicks and the like
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent) -> Option<KeyEvent> {
+    let hit = app.areas.at(mouse.column, mouse.row);
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => handle_click(app, hit),
+        MouseEventKind::ScrollDown => {
+            handle_scroll(app, hit, 1);
+            None
+        }
+        MouseEventKind::ScrollUp => {
+            handle_scroll(app, hit, -1);
+            None
+        }
+        _ => None,
+    }
+}
+
+fn handle_click(app: &mut App, hit: Option<(Region, Option<usize>)>) -> Option<KeyEvent> {
+    if app.message.is_some() {
+        app.message = None;
+        return None;
+    }
+
+    let (region, index) = match hit {
+        Some((region, Some(index))) => (region, index),
+        _ => return None,
+    };
+
+    // with a popup open only its list takes clicks
+    if block_binds(app) && region != Region::Popup {
+        return None;
+    }
+
+    let double = matches!(app.last_click,
+        Some((time, last_region, last_index))
+            if time.elapsed() < DOUBLE_CLICK && last_region == region && last_index == index);
+
+    // a third click starts over
+    app.last_click = if double {
+        None
+    } else {
+        Some((Instant::now(), region, index))
+    };
+
+    match region {
+        Region::Popup => match popup(app) {
+            Some((state, len)) if index < len => state.select(Some(index)),
+            _ => return None,
+        },
+        Region::Files | Region::OtherFiles => {
+            if region == Region::OtherFiles {
+                panes::handle_switch_pane(app);
+            }
+
+            // like `1` when there's nothing there
+            let index = index.min(app.files.items.len().saturating_sub(1));
+            app.files.state.select(Some(index));
+            app.dirs.state.select(None);
+        }
+        Region::Dirs | Region::OtherDirs => {
+            if region == Region::OtherDirs {
+                panes::handle_switch_pane(app);
+            }
+
+            if index >= app.dirs.items.len() {
+                return None;
+            }
+            app.dirs.state.select(Some(index));
+            app.files.state.select(None);
+        }
+        Region::Current => {
+            let entries = if tree::tree(app) {
+                tree::entries(app)
+            } else {
+                miller::entries(app)
+            };
+            miller::select(app, &entries, index);
+        }
+        Region::Parent => return Some(key(KeyCode::Left)),
+        Region::Preview | Region::Output => return None,
+    }
+
+    double.then(|| key(KeyCode::Enter))
+}
+
+fn handle_scroll(app: &mut App, hit: Option<(Region, Option<usize>)>, step: isize) {
+    let region = match hit {
+        Some((region, _)) => region,
+        None => return,
+    };
+
+    if block_binds(app) && region != Region::Popup && region != Region::Output {
+        return;
+    }
+
+    let motion = if step > 0 { Motion::Down } else { Motion::Up };
+
+    match region {
+        Region::Popup => {
+            if let Some((state, len)) = popup(app) {
+                if len > 0 {
+                    let selected = state.selected().unwrap_or(0) as isize;
+                    state.select(Some((selected + step).rem_euclid(len as isize) as usize));
+                }
+            }
+        }
+        Region::Output => movement::handle_output_scroll(app, step),
+        // scrolling a list that isn't the selected one selects it first
+        Region::Files => {
+            if app.files.state.selected().is_none() {
+                movement::handle_pane_switching(app, 1);
+            } else {
+                movement::handle_motion(app, motion, None);
+            }
+        }
+        Region::Dirs => {
+            if app.dirs.state.selected().is_none() {
+                movement::handle_pane_switching(app, 2);
+            } else {
+                movement::handle_motion(app, motion, None);
+            }
+        }
+        Region::Current => movement::handle_motion(app, motion, None),
+        Region::Preview => {
+            app.preview_scroll.1 = if step > 0 {
+                app.preview_scroll.1 + PREVIEW_SCROLL
+            } else {
+                app.preview_scroll.1.saturating_sub(PREVIEW_SCROLL)
+            };
+        }
+        Region::OtherFiles | Region::OtherDirs | Region::Parent => {}
+    }
+}
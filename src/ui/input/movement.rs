@@ -1,56 +1,161 @@
+use super::miller::{normalize, select};
+use super::mouse::Region;
+use super::stateful_list::StatefulList;
+use super::{miller, tree};
+use crate::app::layout::PaneLayout;
 use crate::{app::app::App, ui::display::block::block_binds};
 
-pub fn handle_movement(app: &mut App, key: char) {
+// counts stop growing here, there's never more to move through
+const MAX_COUNT: usize = 99_999;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Motion {
+    Down,
+    Up,
+    // `gg` and `G`, or to the count's row
+    First,
+    Last,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    // `H`, `M` and `L`, the rows on screen
+    ScreenTop,
+    ScreenMiddle,
+    ScreenBottom,
+}
+
+// the rows the cursor moves through, the same way for every layout
+fn rows(app: &mut App) -> Vec<(String, bool)> {
+    if miller::miller(app) {
+        miller::entries(app)
+    } else if tree::tree(app) {
+        tree::entries(app)
+    } else if app.files.state.selected().is_some() {
+        app.files
+            .items
+            .iter()
+            .map(|item| (item.0.clone(), false))
+            .collect()
+    } else {
+        app.dirs
+            .items
+            .iter()
+            .map(|item| (item.0.clone(), true))
+            .collect()
+    }
+}
+
+// the first row on screen and how many fit, from the last frame
+fn screen(app: &App) -> (usize, usize) {
+    let region = if miller::miller(app) || tree::tree(app) {
+        Region::Current
+    } else if app.files.state.selected().is_some() {
+        Region::Files
+    } else {
+        Region::Dirs
+    };
+
+    match app.areas.list(region) {
+        Some((area, offset)) => (offset, (area.height as usize).saturating_sub(2).max(1)),
+        None => (0, 1),
+    }
+}
+
+// `count` repeats the motion, or picks the row for `gg`, `G`, `H` and `L`
+pub fn handle_motion(app: &mut App, motion: Motion, count: Option<usize>) {
     if block_binds(app) {
         return;
     }
 
-    if app.files.state.selected().is_some() {
-        if app.files.items.len() > 1 {
-            if key == 'j' {
-                app.files.next();
-            } else {
-                app.files.previous();
-            }
-        }
-    } else if app.dirs.state.selected().is_some() {
-        if app.dirs.items.len() > 1 {
-            if key == 'j' {
-                app.dirs.next();
-            } else {
-                app.dirs.previous();
-            }
-        }
-    }
+    let rows = rows(app);
+    let current = match normalize(app, &rows) {
+        Some(current) => current,
+        None => return,
+    };
+
+    let index = target(motion, count, current, rows.len(), screen(app), app.wrap);
+    select(app, &rows, index);
 }
 
-pub fn handle_fzf_movement(app: &mut App, idx: isize) {
-    let results = app.fzf_results.items.len();
+// where a motion from `current` lands among `len` rows, with `screen` the
+// first row on screen and how many fit
+fn target(
+    motion: Motion,
+    count: Option<usize>,
+    current: usize,
+    len: usize,
+    screen: (usize, usize),
+    wrap: bool,
+) -> usize {
+    let last = len - 1;
+    let (top, height) = screen;
+    // the rows may have shrunk since the last frame
+    let top = top.min(last);
+    let bottom = (top + height - 1).min(last);
+    // further than every row only matters when wrapping round
+    let times = count.unwrap_or(1).min(len);
+    let lines = match wrap {
+        true => count.unwrap_or(1) % len,
+        false => times,
+    };
 
-    if results > 0 {
-        if app.fzf_results.state.selected().is_none() {
-            app.fzf_results.state.select(Some(0));
-        } else {
-            let selected = app.fzf_results.state.selected().unwrap() as isize;
-            let new_selected = (selected + idx).rem_euclid(results as isize) as usize;
+    let step = |by: isize, wrap: bool| {
+        let index = current as isize + by;
 
-            app.fzf_results.state.select(Some(new_selected));
+        if wrap {
+            index.rem_euclid(len as isize) as usize
+        } else {
+            index.clamp(0, last as isize) as usize
         }
+    };
+
+    let index = match motion {
+        Motion::Down => step(lines as isize, wrap),
+        Motion::Up => step(-(lines as isize), wrap),
+        Motion::First => count.map(|row| row - 1).unwrap_or(0),
+        Motion::Last => count.map(|row| row - 1).unwrap_or(last),
+        Motion::HalfPageDown => step((height / 2 * times).max(1) as isize, false),
+        Motion::HalfPageUp => step(-((height / 2 * times).max(1) as isize), false),
+        Motion::PageDown => step((height * times) as isize, false),
+        Motion::PageUp => step(-((height * times) as isize), false),
+        Motion::ScreenTop => (top + times - 1).min(bottom),
+        Motion::ScreenMiddle => top + (bottom - top) / 2,
+        Motion::ScreenBottom => bottom.saturating_sub(times - 1).max(top),
+    };
+
+    index.min(last)
+}
+
+// digits before a motion, `0` only counts after another digit
+pub fn handle_count(app: &mut App, digit: u32) {
+    if block_binds(app) || (digit == 0 && app.count.is_none()) {
+        return;
     }
+
+    let count = app.count.unwrap_or(0) * 10 + digit as usize;
+    app.count = Some(count.min(MAX_COUNT));
+}
+
+// `1` and `2` pick the files or directories pane where there's one of each,
+// unless they carry on a count
+pub fn pane_key(app: &App, key: char) -> bool {
+    let panes = matches!(app.layout, PaneLayout::Default | PaneLayout::Dual);
+    panes && app.count.is_none() && matches!(key, '1' | '2')
 }
 
-pub fn handle_bookmark_movement(app: &mut App, idx: isize) {
-    let results = app.bookmarked_dirs.items.len();
+// the next or previous item of a popup's list, round from the last to the
+// first
+pub fn move_selection<T>(list: &mut StatefulList<T>, idx: isize) {
+    let results = list.items.len();
 
     if results > 0 {
-        if app.bookmarked_dirs.state.selected().is_none() {
-            app.bookmarked_dirs.state.select(Some(0));
-        } else {
-            let selected = app.bookmarked_dirs.state.selected().unwrap() as isize;
-            let new_selected = (selected + idx).rem_euclid(results as isize) as usize;
+        let new_selected = match list.state.selected() {
+            Some(selected) => (selected as isize + idx).rem_euclid(results as isize) as usize,
+            None => 0,
+        };
 
-            app.bookmarked_dirs.state.select(Some(new_selected));
-        }
+        list.state.select(Some(new_selected));
     }
 }
 
@@ -68,17 +173,77 @@ pub fn handle_pane_switching(app: &mut App, key: u8) {
     }
 }
 
-pub fn handle_ops_menu_movement(app: &mut App, idx: isize) {
-    let results = app.ops_menu.items.len();
+pub fn handle_output_scroll(app: &mut App, idx: isize) {
+    let last = app.output.len().saturating_sub(1) as isize;
+    app.output_scroll = (app.output_scroll as isize + idx).clamp(0, last) as usize;
+}
 
-    if results > 0 {
-        if app.ops_menu.state.selected().is_none() {
-            app.ops_menu.state.select(Some(0));
-        } else {
-            let selected = app.ops_menu.state.selected().unwrap() as isize;
-            let new_selected = (selected + idx).rem_euclid(results as isize) as usize;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            app.ops_menu.state.select(Some(new_selected));
-        }
+    // 100 rows, on row 50, with rows 40 to 59 on screen
+    fn from_50(motion: Motion, count: Option<usize>, wrap: bool) -> usize {
+        target(motion, count, 50, 100, (40, 20), wrap)
+    }
+
+    #[test]
+    fn steps_and_counts() {
+        assert_eq!(from_50(Motion::Down, None, false), 51);
+        assert_eq!(from_50(Motion::Up, Some(5), false), 45);
+        assert_eq!(from_50(Motion::Down, Some(80), false), 99);
+        assert_eq!(from_50(Motion::Up, Some(80), false), 0);
+        assert_eq!(from_50(Motion::Down, Some(80), true), 30);
+        assert_eq!(from_50(Motion::Up, Some(251), true), 99);
+    }
+
+    #[test]
+    fn huge_counts_dont_overflow() {
+        let count = Some(usize::MAX);
+
+        assert_eq!(from_50(Motion::Down, count, false), 99);
+        assert_eq!(from_50(Motion::Up, count, false), 0);
+        assert_eq!(
+            from_50(Motion::Down, count, true),
+            (50 + usize::MAX % 100) % 100
+        );
+        assert_eq!(from_50(Motion::PageDown, count, false), 99);
+        assert_eq!(from_50(Motion::HalfPageUp, count, false), 0);
+        assert_eq!(from_50(Motion::ScreenTop, count, false), 59);
+        assert_eq!(from_50(Motion::ScreenBottom, count, false), 40);
+        assert_eq!(from_50(Motion::Last, count, false), 99);
+    }
+
+    #[test]
+    fn first_and_last_go_to_the_counted_row() {
+        assert_eq!(from_50(Motion::First, None, false), 0);
+        assert_eq!(from_50(Motion::Last, None, false), 99);
+        assert_eq!(from_50(Motion::First, Some(10), false), 9);
+        assert_eq!(from_50(Motion::Last, Some(10), false), 9);
+        assert_eq!(from_50(Motion::First, Some(500), false), 99);
+    }
+
+    #[test]
+    fn pages_never_wrap() {
+        assert_eq!(from_50(Motion::HalfPageDown, None, true), 60);
+        assert_eq!(from_50(Motion::HalfPageUp, Some(2), true), 30);
+        assert_eq!(from_50(Motion::PageDown, None, true), 70);
+        assert_eq!(from_50(Motion::PageDown, Some(3), true), 99);
+        assert_eq!(from_50(Motion::PageUp, Some(3), true), 0);
+        // a single row on screen still moves
+        assert_eq!(target(Motion::HalfPageDown, None, 5, 10, (5, 1), false), 6);
+    }
+
+    #[test]
+    fn screen_rows() {
+        assert_eq!(from_50(Motion::ScreenTop, None, false), 40);
+        assert_eq!(from_50(Motion::ScreenTop, Some(3), false), 42);
+        assert_eq!(from_50(Motion::ScreenMiddle, None, false), 49);
+        assert_eq!(from_50(Motion::ScreenBottom, None, false), 59);
+        assert_eq!(from_50(Motion::ScreenBottom, Some(3), false), 57);
+
+        // fewer rows than fit, and a stale offset after the rows shrank
+        assert_eq!(target(Motion::ScreenBottom, None, 2, 5, (0, 20), false), 4);
+        assert_eq!(target(Motion::ScreenMiddle, None, 2, 5, (40, 20), false), 4);
     }
 }
@@ -1,3 +1,4 @@
+use super::line_editor::LineEditor;
 use super::stateful_list::StatefulList;
 use super::*;
 use crate::app::app::App;
@@ -19,11 +20,187 @@ pub fn handle_nav(app: &mut App, input_active: &mut bool) {
         app.show_nav = true;
         *input_active = true;
         app.last_command = Some(Command::ShowNav);
+        app.nav_query = None;
     }
 }
 
-fn fzf(app: &mut App, input: &mut String) -> Vec<PathBuf> {
-    let query = input.clone();
+// `~` and `$VAR`/`${VAR}`, unset variables are left as they are
+pub fn expand_path(input: &str) -> PathBuf {
+    let mut expanded = String::new();
+    let mut chars = input.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if braced && c == '}' {
+                chars.next();
+                break;
+            }
+            // unbraced names end at the first character that can't be in one
+            if !(braced || c.is_ascii_alphanumeric() || c == '_') {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+
+        match std::env::var(&name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ if braced => expanded.push_str(&format!("${{{}}}", name)),
+            _ => expanded.push_str(&format!("${}", name)),
+        }
+    }
+
+    let home = dirs::home_dir().unwrap_or_default();
+
+    if expanded == "~" {
+        home
+    } else if let Some(rest) = expanded.strip_prefix("~/") {
+        home.join(rest)
+    } else {
+        PathBuf::from(expanded)
+    }
+}
+
+// directories the last segment of the input could be completed to, as the
+// whole input so `~` and variables are kept as typed
+fn suggestions(app: &App, input: &str) -> Vec<String> {
+    let (head, prefix) = match input.rfind('/') {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    };
+
+    let dir = if head.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_path(head)
+    };
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    // smart case, like most shells
+    let ignore_case = !prefix.chars().any(|c| c.is_uppercase());
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.') || app.show_hidden || prefix.starts_with('.'))
+        .filter(|name| {
+            if ignore_case {
+                name.to_lowercase().starts_with(prefix)
+            } else {
+                name.starts_with(prefix)
+            }
+        })
+        .collect();
+
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| format!("{}{}/", head, name))
+        .collect()
+}
+
+// called after every key while the navigator is open, only lists the
+// directory again when the input changed
+pub fn update_nav(app: &mut App, input: &str) {
+    if app.nav_query.as_deref() == Some(input) {
+        return;
+    }
+
+    app.nav_suggestions = StatefulList::with_items(suggestions(app, input));
+    app.nav_query = Some(input.to_string());
+}
+
+// completes as far as the suggestions agree, after that each press moves
+// through them
+pub fn handle_nav_complete(app: &mut App, input: &mut LineEditor, idx: isize) {
+    update_nav(app, input);
+
+    let items = &app.nav_suggestions.items;
+    if items.is_empty() {
+        return;
+    }
+
+    let mut common = items[0].clone();
+    for item in &items[1..] {
+        while !item.starts_with(&common) {
+            common.pop();
+        }
+    }
+
+    if items.len() == 1 || common.chars().count() > input.chars().count() {
+        input.set(common);
+        update_nav(app, input);
+        return;
+    }
+
+    let len = items.len() as isize;
+    let selected = match app.nav_suggestions.state.selected() {
+        Some(selected) => (selected as isize + idx).rem_euclid(len),
+        None if idx < 0 => len - 1,
+        None => 0,
+    };
+    app.nav_suggestions.state.select(Some(selected as usize));
+}
+
+pub fn handle_nav_submit(app: &mut App, input: &str) -> bool {
+    // a suggestion picked with tab wins over what was typed
+    let target = app
+        .nav_suggestions
+        .state
+        .selected()
+        .and_then(|selected| app.nav_suggestions.items.get(selected).cloned())
+        .unwrap_or_else(|| input.to_string());
+
+    if target.trim().is_empty() {
+        return true;
+    }
+
+    let path = expand_path(&target);
+
+    if !path.is_dir() {
+        app.message = Some(format!("No such directory: {}", path.display()));
+        return false;
+    }
+
+    jump::leave(app);
+
+    if let Err(err) = std::env::set_current_dir(&path) {
+        app.message = Some(format!("Cannot open {}: {}", path.display(), err));
+        jump::stay(app);
+        return false;
+    }
+
+    app.archive = None;
+    app.cur_dir = get_pwd();
+
+    app.update_files();
+    app.update_dirs();
+
+    if !jump::arrive(app) {
+        app.files.state.select(Some(0));
+        app.dirs.state.select(None);
+    }
+
+    true
+}
+
+fn fzf(app: &mut App, query: &str) -> Vec<PathBuf> {
     let dir = app.cur_dir.clone();
     let dir = dir.trim_end_matches('\n');
 
@@ -58,7 +235,7 @@ fn fzf(app: &mut App, input: &mut String) -> Vec<PathBuf> {
 
             let filename = entry.file_name().to_str().unwrap().to_string();
 
-            if let Some(matched) = best_match(&query, &filename) {
+            if let Some(matched) = best_match(query, &filename) {
                 if matched.score() > 0 {
                     result.push(entry.path().to_path_buf());
                 }
@@ -69,12 +246,21 @@ fn fzf(app: &mut App, input: &mut String) -> Vec<PathBuf> {
     result
 }
 
-pub fn handle_fzf(app: &mut App, input: &mut String, input_active: &mut bool) {
+pub fn handle_fzf(app: &mut App, input_active: &mut bool) {
     app.show_fzf = true;
     app.show_popup = true;
     app.last_command = Some(Command::ShowFzf);
+    app.fzf_query = None;
 
     *input_active = true;
+}
+
+// called after every key while fzf is open, only searches again when the
+// input changed
+pub fn update_fzf(app: &mut App, input: &str) {
+    if app.fzf_query.as_deref() == Some(input) {
+        return;
+    }
 
     let result = fzf(app, input);
 
@@ -84,6 +270,7 @@ pub fn handle_fzf(app: &mut App, input: &mut String, input_active: &mut bool) {
             .map(|x| x.to_str().unwrap().to_string())
             .collect(),
     );
+    app.fzf_query = Some(input.to_string());
 }
 
 pub fn abbreviate_path(path: &str) -> String {
@@ -124,3 +311,47 @@ pub fn output_cur_dir() {
     stdout().flush().unwrap();
     exit(0);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_home_and_variables() {
+        let home = dirs::home_dir().unwrap_or_default();
+        std::env::set_var("TRAVERSE_TEST_DIR", "/srv/data");
+        std::env::remove_var("TRAVERSE_TEST_UNSET");
+
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path(" ~/notes "), home.join("notes"));
+        assert_eq!(
+            expand_path("$TRAVERSE_TEST_DIR/logs"),
+            PathBuf::from("/srv/data/logs")
+        );
+        assert_eq!(
+            expand_path("${TRAVERSE_TEST_DIR}x"),
+            PathBuf::from("/srv/datax")
+        );
+        assert_eq!(
+            expand_path("$TRAVERSE_TEST_DIR-old"),
+            PathBuf::from("/srv/data-old")
+        );
+    }
+
+    #[test]
+    fn leaves_unset_variables_and_other_tildes_alone() {
+        std::env::remove_var("TRAVERSE_TEST_UNSET");
+
+        assert_eq!(
+            expand_path("$TRAVERSE_TEST_UNSET/a"),
+            PathBuf::from("$TRAVERSE_TEST_UNSET/a")
+        );
+        assert_eq!(
+            expand_path("${TRAVERSE_TEST_UNSET}"),
+            PathBuf::from("${TRAVERSE_TEST_UNSET}")
+        );
+        assert_eq!(expand_path("a/~/b"), PathBuf::from("a/~/b"));
+        assert_eq!(expand_path("~user"), PathBuf::from("~user"));
+        assert_eq!(expand_path("cost$"), PathBuf::from("cost$"));
+    }
+}
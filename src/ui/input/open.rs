@@ -0,0 +1,263 @@
+use super::stateful_list::StatefulList;
+use crate::app::app::App;
+use crate::filetype::filetype::classify;
+use crate::ui::display::block::block_binds;
+use glob::Pattern;
+use std::path::Path;
+use std::process::{Command as SysCommand, Stdio};
+
+// a rule from `opener=<pattern>|<command>|<terminal|detach>` in the config,
+// the pattern is a MIME type (`text/*`), an extension (`.rs`) or a glob (`*.tar.gz`)
+#[derive(Clone)]
+pub struct Opener {
+    pub pattern: String,
+    pub command: String,
+    pub terminal: bool,
+}
+
+impl Opener {
+    pub fn parse(value: &str) -> Option<Opener> {
+        let (pattern, rest) = value.split_once('|')?;
+        let pattern = pattern.trim().to_string();
+
+        // the command itself may contain pipes, so the mode is only
+        // taken from the end when it's one of the known ones
+        let (command, terminal) = match rest.rsplit_once('|') {
+            Some((command, mode)) if mode.trim().eq_ignore_ascii_case("terminal") => {
+                (command, true)
+            }
+            Some((command, mode)) if mode.trim().eq_ignore_ascii_case("detach") => (command, false),
+            _ => (rest, false),
+        };
+        let command = command.trim().to_string();
+
+        if pattern.is_empty() || command.is_empty() {
+            return None;
+        }
+
+        Some(Opener {
+            pattern,
+            command,
+            terminal,
+        })
+    }
+
+    pub fn matches(&self, name: &str, mime: &str) -> bool {
+        matches_pattern(&self.pattern, name, mime)
+    }
+
+    pub fn label(&self) -> String {
+        let mode = if self.terminal { "terminal" } else { "detach" };
+        format!("{}  ({}, {})", self.command, self.pattern, mode)
+    }
+}
+
+// a MIME type (`text/*`), an extension (`.rs`) or a glob on the name (`*.tar.gz`)
+pub fn matches_pattern(pattern: &str, name: &str, mime: &str) -> bool {
+    if let Some(ext) = pattern.strip_prefix('.') {
+        return Path::new(name)
+            .extension()
+            .map(|e| e.to_string_lossy().eq_ignore_ascii_case(ext))
+            .unwrap_or(false);
+    }
+
+    let subject = if pattern.contains('/') { mime } else { name };
+
+    Pattern::new(pattern)
+        .map(|pattern| pattern.matches(subject))
+        .unwrap_or(false)
+}
+
+pub fn default_opener() -> Opener {
+    let command = if cfg!(target_os = "macos") {
+        "open {}"
+    } else {
+        "xdg-open {}"
+    };
+
+    Opener {
+        pattern: "*".to_string(),
+        command: command.to_string(),
+        terminal: false,
+    }
+}
+
+// configured rules in order, with the system opener always last
+pub fn matching_openers(app: &App, file: &str) -> Vec<Opener> {
+    let mime = classify(Path::new(file)).mime;
+
+    let mut openers: Vec<Opener> = app
+        .openers
+        .iter()
+        .filter(|opener| opener.matches(file, &mime))
+        .cloned()
+        .collect();
+
+    openers.push(default_opener());
+    openers
+}
+
+// whether a configured rule takes the file, archives are only browsed when
+// none does
+pub fn has_opener(app: &App, file: &str) -> bool {
+    let mime = classify(Path::new(file)).mime;
+    app.openers.iter().any(|opener| opener.matches(file, &mime))
+}
+
+pub fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+// `{}` is the highlighted file, `$@` the staged selection (or the
+// highlighted file when nothing is staged)
+pub fn build_command(template: &str, file: &str, selection: &[String]) -> SysCommand {
+    let script = template.replace("{}", &quote(file));
+
+    let mut command = SysCommand::new("sh");
+    command.arg("-c").arg(script).arg("sh");
+
+    if selection.is_empty() {
+        command.arg(file);
+    } else {
+        command.args(selection);
+    }
+
+    command
+}
+
+fn highlighted_file(app: &App) -> Option<String> {
+    let selected = app.files.state.selected()?;
+    let file = app.files.items.get(selected)?;
+    let cur_dir = std::env::current_dir().ok()?;
+
+    Some(cur_dir.join(&file.0).to_string_lossy().to_string())
+}
+
+pub fn run_opener(app: &mut App, opener: &Opener, file: &str) {
+    let mut command = build_command(&opener.command, file, &app.selected_files);
+
+    if opener.terminal {
+        // run_app suspends the ui while this runs
+        app.run_in_terminal = Some(command);
+        return;
+    }
+
+    let spawned = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    match spawned {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => {
+            app.message = Some(format!("Failed to run {}: {}", opener.command, err));
+        }
+    }
+}
+
+pub fn handle_open(app: &mut App) {
+    if let Some(file) = highlighted_file(app) {
+        let opener = matching_openers(app, &file).remove(0);
+        run_opener(app, &opener, &file);
+    }
+}
+
+pub fn handle_open_with(app: &mut App) {
+    if block_binds(app) || app.archive.is_some() {
+        return;
+    }
+
+    if let Some(file) = highlighted_file(app) {
+        app.open_with = StatefulList::with_items(matching_openers(app, &file));
+        app.open_with.state.select(Some(0));
+        app.show_open_with = true;
+    }
+}
+
+pub fn handle_open_with_submit(app: &mut App) {
+    let opener = app
+        .open_with
+        .state
+        .selected()
+        .and_then(|selected| app.open_with.items.get(selected).cloned());
+
+    app.show_open_with = false;
+    app.last_command = None;
+
+    if let (Some(opener), Some(file)) = (opener, highlighted_file(app)) {
+        run_opener(app, &opener, &file);
+    }
+}
+
+// `$VISUAL`, then `$EDITOR`, on the staged files or the highlighted one
+pub fn handle_edit(app: &mut App) {
+    if block_binds(app) || app.archive.is_some() {
+        return;
+    }
+
+    let file = match highlighted_file(app) {
+        Some(file) => file,
+        None if !app.selected_files.is_empty() => app.selected_files[0].clone(),
+        None => return,
+    };
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    app.run_in_terminal = Some(build_command(
+        &format!("{} \"$@\"", editor),
+        &file,
+        &app.selected_files,
+    ));
+}
+
+pub fn handle_shell(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    app.run_in_terminal = Some(SysCommand::new(shell));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_opener_rules() {
+        let opener = Opener::parse(" text/* | nvim {} | terminal ").unwrap();
+        assert_eq!(opener.pattern, "text/*");
+        assert_eq!(opener.command, "nvim {}");
+        assert!(opener.terminal);
+
+        let opener = Opener::parse(".pdf|zathura {}|DETACH").unwrap();
+        assert_eq!(opener.command, "zathura {}");
+        assert!(!opener.terminal);
+
+        // a pipe in the command isn't taken for the mode
+        let opener = Opener::parse("*.log|cat {} | less").unwrap();
+        assert_eq!(opener.command, "cat {} | less");
+        assert!(!opener.terminal);
+
+        assert!(Opener::parse("*.log").is_none());
+        assert!(Opener::parse(" |less").is_none());
+        assert!(Opener::parse(".pdf| |terminal").is_none());
+    }
+
+    #[test]
+    fn matches_mime_types_extensions_and_globs() {
+        let opener = |pattern: &str| Opener::parse(&format!("{}|true", pattern)).unwrap();
+
+        assert!(opener("text/*").matches("notes", "text/plain"));
+        assert!(!opener("image/*").matches("notes", "text/plain"));
+        assert!(opener(".PDF").matches("paper.pdf", "application/pdf"));
+        assert!(!opener(".pdf").matches("pdf", "application/pdf"));
+        assert!(opener("*.tar.gz").matches("backup.tar.gz", "application/gzip"));
+        assert!(!opener("*.tar.gz").matches("backup.gz", "application/gzip"));
+    }
+}
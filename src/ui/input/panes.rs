@@ -0,0 +1,158 @@
+use super::tabs::{apply, take_current, transfer};
+use crate::app::app::App;
+use crate::app::layout::PaneLayout;
+use crate::app::tab::{Listing, Tab};
+use crate::ui::display::block::block_binds;
+use std::fs;
+use std::path::Path;
+
+fn dual(app: &App) -> bool {
+    app.layout == PaneLayout::Dual
+}
+
+// the other side starts out in the same directory as this one
+pub fn other_pane(app: &mut App) -> &mut Tab {
+    app.other
+        .get_or_insert_with(|| Tab::new(std::env::current_dir().unwrap_or_default()))
+}
+
+// the other side's files and directories, only read again after a refresh
+// or once something in its directory was added, removed or renamed
+pub fn other_listing(app: &mut App) -> Listing {
+    let tab = other_pane(app);
+    let modified = fs::metadata(&tab.dir)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    if let Some(listing) = &tab.listing {
+        if listing.modified == modified {
+            return listing.clone();
+        }
+    }
+
+    let (dir, filter, sort) = (tab.dir.clone(), tab.filter.clone(), tab.sort);
+    let files = app.list_entries(&dir, false, &filter, sort);
+    let dirs = app.list_entries(&dir, true, &filter, sort);
+
+    let listing = Listing {
+        modified,
+        files,
+        dirs,
+    };
+    other_pane(app).listing = Some(listing.clone());
+
+    listing
+}
+
+pub fn handle_toggle_dual(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    // back to the configured layout, or the usual one if that's this one
+    app.layout = match (app.layout, app.configured_layout) {
+        (PaneLayout::Dual, PaneLayout::Dual) => PaneLayout::Default,
+        (PaneLayout::Dual, configured) => configured,
+        _ => PaneLayout::Dual,
+    };
+}
+
+// the sides swap their state, so the active one is always the one in `App`
+pub fn handle_switch_pane(app: &mut App) {
+    if block_binds(app) || !dual(app) {
+        return;
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let other = app.other.take().unwrap_or_else(|| Tab::new(cwd));
+    app.other = Some(take_current(app));
+    app.active_right = !app.active_right;
+
+    apply(app, other);
+}
+
+// F5 and F6, like Midnight Commander
+pub fn handle_transfer(app: &mut App, copy: bool) {
+    if block_binds(app) || !dual(app) || app.archive.is_some() {
+        return;
+    }
+
+    let other = other_pane(app);
+    if other.archive.is_some() {
+        app.message = Some("The other pane is browsing an archive".to_string());
+        return;
+    }
+
+    let dest = other.dir.clone();
+    transfer(app, &dest, copy, "the other pane");
+}
+
+// puts what's missing from the other pane, or differs from its copy there,
+// in the move/copy buffer, ready to be copied over
+pub fn handle_compare(app: &mut App) {
+    if block_binds(app) || !dual(app) || app.archive.is_some() {
+        return;
+    }
+
+    let other = other_pane(app);
+    if other.archive.is_some() {
+        app.message = Some("The other pane is browsing an archive".to_string());
+        return;
+    }
+
+    let dest = other.dir.clone();
+    let cwd = std::env::current_dir().unwrap_or_default();
+
+    let names: Vec<String> = app
+        .files
+        .items
+        .iter()
+        .chain(app.dirs.items.iter())
+        .map(|item| item.0.clone())
+        .filter(|name| name != "../")
+        .collect();
+
+    let mut count = 0;
+    for name in names {
+        let path = cwd.join(&name);
+        let theirs = dest.join(&name);
+        if !differs(&path, &theirs) {
+            continue;
+        }
+
+        let staged = path.to_string_lossy().to_string();
+        if !app.selected_files.contains(&staged) {
+            app.selected_files.push(staged);
+        }
+        if !app.compared.contains(&(path.clone(), theirs.clone())) {
+            app.compared.push((path, theirs));
+        }
+        count += 1;
+    }
+
+    app.message = Some(if count == 0 {
+        "Nothing differs from the other pane".to_string()
+    } else {
+        format!(
+            "{} entries differ from the other pane, press F5 to copy them",
+            count
+        )
+    });
+}
+
+// directories only count as differing when they're missing, files also when
+// their size differs or this copy is newer
+fn differs(ours: &Path, theirs: &Path) -> bool {
+    let (ours, theirs) = match (fs::metadata(ours), fs::metadata(theirs)) {
+        (Ok(ours), Ok(theirs)) => (ours, theirs),
+        (Ok(_), Err(_)) => return true,
+        _ => return false,
+    };
+
+    if ours.is_dir() || theirs.is_dir() {
+        return ours.is_dir() != theirs.is_dir();
+    }
+
+    ours.len() != theirs.len()
+        || matches!((ours.modified(), theirs.modified()), (Ok(a), Ok(b)) if a > b)
+}
@@ -1,7 +1,10 @@
+use super::line_editor::LineEditor;
+use super::movement::Motion;
 use super::*;
 use crate::app::app::App;
+use crate::app::layout::PaneLayout;
 use crate::ui::display::block::block_binds;
-use crate::ui::display::render::render;
+use crate::ui::display::render::{render, suspend};
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::backend::Backend;
@@ -9,7 +12,7 @@ use ratatui::terminal::Terminal;
 use std::process::Command as SysCommand;
 use std::time::Duration;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Command {
     CreateFile,
     CreateDir,
@@ -19,6 +22,13 @@ pub enum Command {
     ShowFzf,
     ShowHelp,
     Bookmark,
+    CreateArchive,
+    ExtractArchive,
+    ZipPassword,
+    RunShell,
+    ConfirmCustom,
+    Jump,
+    Filter,
 }
 
 pub fn run_app<B: Backend>(
@@ -27,10 +37,12 @@ pub fn run_app<B: Backend>(
     tick_rate: Duration,
 ) -> Result<()> {
     let mut last_tick = std::time::Instant::now();
-    let mut input = String::new();
+    let mut input = LineEditor::default();
     let mut input_active = false;
 
     loop {
+        app.poll_jobs();
+        app.frecency.flush(false);
         terminal.draw(|f| render(f, &mut app, &mut input))?;
 
         let timeout = tick_rate
@@ -38,9 +50,40 @@ pub fn run_app<B: Backend>(
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+
+            // clicks that stand for a key, like double clicks for Enter, go
+            // through the key bindings
+            let event = match event {
+                Event::Mouse(event) => match mouse::handle_mouse(&mut app, event) {
+                    Some(key) => Event::Key(key),
+                    None => Event::Mouse(event),
+                },
+                event => event,
+            };
+
+            if let Event::Paste(text) = &event {
+                if input_active {
+                    input.insert_str(text);
+                }
+            }
+
+            if let Event::Key(key) = event {
                 if key.kind == KeyEventKind::Press {
+                    let control = key.modifiers.contains(event::KeyModifiers::CONTROL);
+                    let alt = key.modifiers.contains(event::KeyModifiers::ALT);
+
+                    // a count or a `g` only lasts until the next key
+                    let count = match key.code {
+                        KeyCode::Char(c) if c.is_ascii_digit() && !alt => None,
+                        _ => app.count.take(),
+                    };
+                    let pending_g = std::mem::take(&mut app.pending_g);
+
                     match key.code {
+                        // typing in a prompt, anything else goes on to the bindings
+                        _ if input_active && input.handle_key(key) => {}
+
                         // EXIT
                         KeyCode::Char('c')
                             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
@@ -48,17 +91,25 @@ pub fn run_app<B: Backend>(
                             SysCommand::new("reset").status().unwrap_or_else(|_| {
                                 panic!("Failed to reset terminal");
                             });
+                            app.frecency.flush(true);
                             nav::output_cur_dir();
 
                             return Ok(());
                         }
                         KeyCode::Esc => {
-                            if app.show_popup
+                            if app.message.is_some() {
+                                app.message = None;
+                            } else if app.show_popup
                                 || app.show_nav
                                 || app.show_fzf
                                 || app.show_bookmark
                                 || app.show_help
                                 || app.show_ops_menu
+                                || app.show_open_with
+                                || app.show_output
+                                || app.show_custom_menu
+                                || app.show_recent
+                                || app.show_jump
                             {
                                 input_active = false;
                                 app.show_popup = false;
@@ -68,172 +119,329 @@ pub fn run_app<B: Backend>(
                                 app.show_bookmark = false;
                                 app.show_help = false;
                                 app.show_ops_menu = false;
+                                app.show_open_with = false;
+                                app.show_output = false;
+                                app.show_custom_menu = false;
+                                app.pending_custom = None;
+                                app.show_recent = false;
+                                app.show_jump = false;
                                 input.clear();
                             } else {
                                 SysCommand::new("reset").status().unwrap_or_else(|_| {
                                     panic!("Failed to reset terminal");
                                 });
+                                app.frecency.flush(true);
                                 nav::output_cur_dir();
 
                                 return Ok(());
                             }
                         }
                         KeyCode::Char('q') => {
-                            if app.show_fzf || app.show_nav || input_active {
-                                input.push('q');
+                            if app.show_popup
+                                || app.show_nav
+                                || app.show_fzf
+                                || app.show_bookmark
+                                || app.show_help
+                                || app.show_ops_menu
+                                || app.show_open_with
+                                || app.show_output
+                                || app.show_custom_menu
+                                || app.show_recent
+                                || app.show_jump
+                            {
+                                input_active = false;
+                                app.show_popup = false;
+                                app.show_nav = false;
+                                app.show_fzf = false;
+                                app.last_command = None;
+                                app.show_bookmark = false;
+                                app.show_help = false;
+                                app.show_ops_menu = false;
+                                app.show_open_with = false;
+                                app.show_output = false;
+                                app.show_custom_menu = false;
+                                app.pending_custom = None;
+                                app.show_recent = false;
+                                app.show_jump = false;
+                                input.clear();
                             } else {
-                                if app.show_popup
-                                    || app.show_nav
-                                    || app.show_fzf
-                                    || app.show_bookmark
-                                    || app.show_help
-                                    || app.show_ops_menu
-                                {
-                                    input_active = false;
-                                    app.show_popup = false;
-                                    app.show_nav = false;
-                                    app.show_fzf = false;
-                                    app.last_command = None;
-                                    app.show_bookmark = false;
-                                    app.show_help = false;
-                                    app.show_ops_menu = false;
-                                    input.clear();
-                                } else {
-                                    SysCommand::new("reset").status().unwrap_or_else(|_| {
-                                        panic!("Failed to reset terminal");
-                                    });
+                                SysCommand::new("reset").status().unwrap_or_else(|_| {
+                                    panic!("Failed to reset terminal");
+                                });
 
-                                    nav::output_cur_dir();
-                                    return Ok(());
-                                }
+                                app.frecency.flush(true);
+                                nav::output_cur_dir();
+                                return Ok(());
                             }
                         }
 
+                        // COUNTS & PREFIXES
+                        KeyCode::Char(c)
+                            if c.is_ascii_digit() && !alt && !movement::pane_key(&app, c) =>
+                        {
+                            movement::handle_count(&mut app, c.to_digit(10).unwrap());
+                        }
+                        KeyCode::Char('g') if pending_g => {
+                            movement::handle_motion(&mut app, Motion::First, count);
+                        }
+                        KeyCode::Char('t') if pending_g => match count {
+                            Some(n) => tabs::handle_goto_tab(&mut app, n),
+                            None => tabs::handle_cycle_tab(&mut app, 1),
+                        },
+                        KeyCode::Char('T') if pending_g => {
+                            tabs::handle_cycle_tab(&mut app, -1);
+                        }
+                        KeyCode::Char('g') if !block_binds(&mut app) => {
+                            app.pending_g = true;
+                            app.count = count;
+                        }
+
+                        // TABS
+                        KeyCode::Char(c) if alt && c.is_ascii_digit() => {
+                            tabs::handle_goto_tab(&mut app, c.to_digit(10).unwrap() as usize);
+                        }
+                        KeyCode::Char('t') => {
+                            tabs::handle_new_tab(&mut app);
+                        }
+                        KeyCode::Char('w')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            tabs::handle_close_tab(&mut app);
+                        }
+
+                        // DUAL PANES
+                        KeyCode::Char('|') => {
+                            panes::handle_toggle_dual(&mut app);
+                        }
+                        KeyCode::F(5) => {
+                            panes::handle_transfer(&mut app, true);
+                        }
+                        KeyCode::F(6) => {
+                            panes::handle_transfer(&mut app, false);
+                        }
+                        KeyCode::Char('=') => {
+                            panes::handle_compare(&mut app);
+                        }
+
                         // PANE SWITCHING
                         KeyCode::Char('1') => {
-                            if input_active {
-                                input.push('1');
-                            } else {
-                                movement::handle_pane_switching(&mut app, 1);
-                            }
+                            movement::handle_pane_switching(&mut app, 1);
                         }
                         KeyCode::Char('2') => {
-                            if input_active {
-                                input.push('2');
-                            } else {
-                                movement::handle_pane_switching(&mut app, 2);
-                            }
+                            movement::handle_pane_switching(&mut app, 2);
                         }
 
                         // MOVEMENT
+                        KeyCode::Up if input_active => {
+                            if let Some(prompt) = app.last_command {
+                                input.history_previous(prompt);
+                            }
+                        }
+                        KeyCode::Down if input_active => {
+                            if let Some(prompt) = app.last_command {
+                                input.history_next(prompt);
+                            }
+                        }
                         KeyCode::Char('j') | KeyCode::Down => {
-                            if input_active {
-                                input.push('j');
+                            if app.show_output {
+                                let lines = count.unwrap_or(1) as isize;
+                                movement::handle_output_scroll(&mut app, lines);
                             } else {
-                                movement::handle_movement(&mut app, 'j');
+                                movement::handle_motion(&mut app, Motion::Down, count);
                             }
                         }
                         KeyCode::Char('k') | KeyCode::Up => {
-                            if input_active {
-                                input.push('k');
+                            if app.show_output {
+                                let lines = count.unwrap_or(1) as isize;
+                                movement::handle_output_scroll(&mut app, -lines);
                             } else {
-                                movement::handle_movement(&mut app, 'k');
+                                movement::handle_motion(&mut app, Motion::Up, count);
                             }
                         }
+                        KeyCode::Char('G') => {
+                            movement::handle_motion(&mut app, Motion::Last, count);
+                        }
+                        KeyCode::Char('d') if control && app.show_bookmark => {
+                            bookmark::delete_bookmark(&mut app);
+                        }
+                        KeyCode::Char('d') if control => {
+                            movement::handle_motion(&mut app, Motion::HalfPageDown, count);
+                        }
+                        KeyCode::Char('u') if control => {
+                            movement::handle_motion(&mut app, Motion::HalfPageUp, count);
+                        }
+                        KeyCode::PageDown => {
+                            movement::handle_motion(&mut app, Motion::PageDown, count);
+                        }
+                        KeyCode::PageUp => {
+                            movement::handle_motion(&mut app, Motion::PageUp, count);
+                        }
+                        KeyCode::Char('H') => {
+                            movement::handle_motion(&mut app, Motion::ScreenTop, count);
+                        }
+                        KeyCode::Char('M') => {
+                            movement::handle_motion(&mut app, Motion::ScreenMiddle, count);
+                        }
+                        KeyCode::Char('L') => {
+                            movement::handle_motion(&mut app, Motion::ScreenBottom, count);
+                        }
                         KeyCode::Char('n')
                             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                         {
                             if app.show_fzf && block_binds(&mut app) {
-                                movement::handle_fzf_movement(&mut app, 1);
+                                movement::move_selection(&mut app.fzf_results, 1);
+                            } else if app.show_jump {
+                                movement::move_selection(&mut app.jump_results, 1);
+                            } else if app.show_nav {
+                                movement::move_selection(&mut app.nav_suggestions, 1);
                             } else if app.show_bookmark {
-                                movement::handle_bookmark_movement(&mut app, 1);
+                                movement::move_selection(&mut app.bookmarked_dirs, 1);
                             } else if app.show_ops_menu {
-                                movement::handle_ops_menu_movement(&mut app, 1);
+                                movement::move_selection(&mut app.ops_menu, 1);
+                            } else if app.show_open_with {
+                                movement::move_selection(&mut app.open_with, 1);
+                            } else if app.show_output {
+                                movement::handle_output_scroll(&mut app, 1);
+                            } else if app.show_custom_menu {
+                                movement::move_selection(&mut app.custom_menu, 1);
+                            } else if app.show_recent {
+                                movement::move_selection(&mut app.recent, 1);
                             }
                         }
                         KeyCode::Char('p')
                             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                         {
                             if app.show_fzf && block_binds(&mut app) {
-                                movement::handle_fzf_movement(&mut app, -1);
+                                movement::move_selection(&mut app.fzf_results, -1);
+                            } else if app.show_jump {
+                                movement::move_selection(&mut app.jump_results, -1);
+                            } else if app.show_nav {
+                                movement::move_selection(&mut app.nav_suggestions, -1);
                             } else if app.show_bookmark {
-                                movement::handle_bookmark_movement(&mut app, -1);
+                                movement::move_selection(&mut app.bookmarked_dirs, -1);
                             } else if app.show_ops_menu {
-                                movement::handle_ops_menu_movement(&mut app, -1);
+                                movement::move_selection(&mut app.ops_menu, -1);
+                            } else if app.show_open_with {
+                                movement::move_selection(&mut app.open_with, -1);
+                            } else if app.show_output {
+                                movement::handle_output_scroll(&mut app, -1);
+                            } else if app.show_custom_menu {
+                                movement::move_selection(&mut app.custom_menu, -1);
+                            } else if app.show_recent {
+                                movement::move_selection(&mut app.recent, -1);
                             }
                         }
 
                         // BOOKMARKS
                         KeyCode::Char('z') => {
-                            if input_active {
-                                input.push('z');
-                            } else {
-                                bookmark::add_bookmark(&mut app);
-                            }
+                            bookmark::add_bookmark(&mut app);
                         }
                         KeyCode::Char('b') => {
-                            if input_active {
-                                input.push('b');
-                            } else {
-                                bookmark::handle_bookmark(&mut app);
-                            }
+                            bookmark::handle_bookmark(&mut app);
+                        }
+
+                        // HISTORY
+                        KeyCode::Left if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                            jump::handle_back(&mut app);
+                        }
+                        KeyCode::Right if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                            jump::handle_forward(&mut app);
+                        }
+                        KeyCode::Left | KeyCode::Char('h') if tree::tree(&app) => {
+                            tree::handle_collapse(&mut app, &mut input);
+                        }
+                        KeyCode::Right | KeyCode::Char('l') if tree::tree(&app) => {
+                            tree::handle_expand(&mut app);
+                        }
+                        KeyCode::Left if miller::miller(&app) => {
+                            miller::handle_parent(&mut app, &mut input);
+                        }
+                        KeyCode::Right if miller::miller(&app) => {
+                            miller::handle_enter(&mut app, &mut input);
+                        }
+                        KeyCode::Char('h') if miller::miller(&app) => {
+                            miller::handle_parent(&mut app, &mut input);
+                        }
+                        KeyCode::Char('l') if miller::miller(&app) => {
+                            miller::handle_enter(&mut app, &mut input);
+                        }
+                        // `../` is in the directories, and entering is Enter's job
+                        KeyCode::Char('h') => {
+                            miller::handle_parent(&mut app, &mut input);
+                        }
+                        KeyCode::Char('l') if !block_binds(&mut app) => {
+                            submit::handle_submit(&mut app, &mut input, &mut input_active);
+                        }
+                        KeyCode::Char(' ') if tree::tree(&app) => {
+                            tree::handle_toggle(&mut app);
+                        }
+                        KeyCode::Char('T') => {
+                            tree::handle_tree(&mut app);
+                        }
+                        KeyCode::Char('[') => {
+                            jump::handle_back(&mut app);
+                        }
+                        KeyCode::Char(']') => {
+                            jump::handle_forward(&mut app);
+                        }
+                        KeyCode::Char('\'') => {
+                            jump::handle_recent(&mut app);
+                        }
+                        KeyCode::Char('J') => {
+                            jump::handle_jump(&mut app, &mut input_active);
                         }
 
                         // FILE OPS
                         KeyCode::Char('n') => {
-                            if input_active {
-                                input.push('n');
-                            } else {
-                                file_ops::handle_new_file(&mut app, &mut input_active);
-                            }
+                            file_ops::handle_new_file(&mut app, &mut input_active);
                         }
-                        KeyCode::Char('d')
-                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                        {
-                            if app.show_bookmark {
-                                bookmark::delete_bookmark(&mut app);
-                            } else {
-                                file_ops::handle_delete(&mut app);
-                            }
+                        KeyCode::Char('D') | KeyCode::Delete => {
+                            file_ops::handle_delete(&mut app);
                         }
                         KeyCode::Char('c') => {
-                            if input_active {
-                                input.push('c');
-                            } else {
-                                file_ops::add_to_selected(&mut app);
-                            }
+                            file_ops::add_to_selected(&mut app);
                         }
                         KeyCode::Char('p') => {
-                            if input_active {
-                                input.push('p');
-                            } else {
-                                if app.files.state.selected().is_some()
-                                    || app.dirs.state.selected().is_some()
-                                {
-                                    app.show_ops_menu = true;
-                                }
+                            if (app.files.state.selected().is_some()
+                                || app.dirs.state.selected().is_some())
+                                && app.archive.is_none()
+                            {
+                                tabs::add_tab_operations(&mut app);
+                                app.show_ops_menu = true;
                             }
                         }
                         KeyCode::Char('x') => {
-                            if input_active {
-                                input.push('x');
-                            } else {
-                                file_ops::extract(&mut app);
-                            }
+                            file_ops::extract(&mut app, &mut input, &mut input_active);
+                        }
+                        KeyCode::Char('a') => {
+                            compress::handle_create_archive(
+                                &mut app,
+                                &mut input,
+                                &mut input_active,
+                            );
+                        }
+                        KeyCode::Char('e') => {
+                            open::handle_edit(&mut app);
+                        }
+                        KeyCode::Char('s') => {
+                            open::handle_shell(&mut app);
+                        }
+                        KeyCode::Char('o') => {
+                            open::handle_open_with(&mut app);
+                        }
+                        KeyCode::Char('!') => {
+                            shell::handle_shell_command(&mut app, &mut input, &mut input_active);
+                        }
+                        KeyCode::Char('m') => {
+                            custom::handle_custom_menu(&mut app);
                         }
                         KeyCode::Char('r') => {
-                            if input_active {
-                                input.push('r');
-                            } else {
-                                file_ops::handle_rename(&mut app, &mut input, &mut input_active);
-                            }
+                            file_ops::handle_rename(&mut app, &mut input, &mut input_active);
                         }
 
                         // HELP MENU
                         KeyCode::Char('?') => {
-                            if input_active {
-                                input.push('?');
-                            } else if app.show_help {
+                            if app.show_help {
                                 app.show_help = false;
                                 app.last_command = None;
                             } else {
@@ -241,32 +449,63 @@ pub fn run_app<B: Backend>(
                             }
                         }
 
+                        // FILTER & SORT
+                        KeyCode::Char('/') => {
+                            tabs::handle_filter(&mut app, &mut input, &mut input_active);
+                        }
+                        KeyCode::Char('S') => {
+                            tabs::handle_sort(&mut app);
+                        }
+
                         // FZF & NAV
                         KeyCode::Char('w') => {
-                            if input_active {
-                                input.push('w');
-                            } else {
-                                nav::handle_fzf(&mut app, &mut input, &mut input_active);
-                            }
+                            nav::handle_fzf(&mut app, &mut input_active);
                         }
                         KeyCode::Char('f') => {
-                            if input_active {
-                                input.push('f');
-                            } else {
-                                nav::handle_nav(&mut app, &mut input_active);
-                            }
+                            nav::handle_nav(&mut app, &mut input_active);
+                        }
+
+                        // COMPLETION
+                        KeyCode::Tab if app.show_nav => {
+                            nav::handle_nav_complete(&mut app, &mut input, 1);
+                        }
+                        KeyCode::BackTab if app.show_nav => {
+                            nav::handle_nav_complete(&mut app, &mut input, -1);
+                        }
+                        KeyCode::Tab | KeyCode::BackTab if app.layout == PaneLayout::Dual => {
+                            panes::handle_switch_pane(&mut app);
+                        }
+                        KeyCode::Tab => {
+                            tabs::handle_cycle_tab(&mut app, 1);
+                        }
+                        KeyCode::BackTab => {
+                            tabs::handle_cycle_tab(&mut app, -1);
                         }
 
                         // SUBMIT
                         KeyCode::Enter => {
+                            if let (true, Some(prompt)) = (input_active, app.last_command) {
+                                input.remember(prompt);
+                            }
+
                             if app.show_fzf {
                                 submit::handle_open_fzf_result(
                                     &mut app,
                                     &mut input,
                                     &mut input_active,
                                 );
+                            } else if app.show_jump {
+                                jump::handle_open_jump(&mut app, &mut input, &mut input_active);
                             } else if input_active {
                                 submit::handle_submit(&mut app, &mut input, &mut input_active);
+                            } else if app.show_output {
+                                app.show_output = false;
+                            } else if app.show_open_with {
+                                open::handle_open_with_submit(&mut app);
+                            } else if app.show_custom_menu {
+                                custom::handle_custom_menu_submit(&mut app, &mut input_active);
+                            } else if app.show_recent {
+                                jump::handle_open_recent(&mut app);
                             } else if app.show_bookmark {
                                 submit::handle_open_bookmark(&mut app);
                             } else if app.show_ops_menu {
@@ -281,29 +520,9 @@ pub fn run_app<B: Backend>(
                             }
                         }
 
-                        // BACKSPACE
-                        KeyCode::Backspace => {
-                            if input_active {
-                                input.pop();
-                                if app.show_fzf {
-                                    nav::handle_fzf(&mut app, &mut input, &mut input_active);
-                                }
-                            }
-                        }
-
                         // OTHER CHARACTERS
                         KeyCode::Char(c) => {
-                            if input_active {
-                                input.push(c);
-
-                                if app.last_command == Some(Command::ShowFzf) {
-                                    nav::handle_fzf(&mut app, &mut input, &mut input_active);
-                                }
-
-                                if app.show_fzf {
-                                    nav::handle_fzf(&mut app, &mut input, &mut input_active);
-                                }
-                            }
+                            custom::handle_custom_key(&mut app, c, &mut input_active);
                         }
                         _ => {}
                     }
@@ -311,6 +530,28 @@ pub fn run_app<B: Backend>(
             }
         }
 
+        if app.show_fzf {
+            nav::update_fzf(&mut app, &input);
+        }
+
+        if app.show_jump {
+            jump::update_jump(&mut app, &input);
+        }
+
+        if app.show_nav {
+            nav::update_nav(&mut app, &input);
+        }
+
+        if let Some(mut command) = app.run_in_terminal.take() {
+            if let Err(err) = suspend(terminal, || command.status())? {
+                app.message = Some(format!("Failed to run command: {}", err));
+            }
+
+            // whatever ran may have changed the directory's contents
+            app.update_files();
+            app.update_dirs();
+        }
+
         if last_tick.elapsed() >= tick_rate {
             last_tick = std::time::Instant::now();
         }
@@ -0,0 +1,215 @@
+use super::line_editor::LineEditor;
+use super::open::quote;
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::app::job::{Job, OutputFeed};
+use crate::ui::display::block::block_binds;
+use dirs::config_dir;
+use std::fs::{self, OpenOptions};
+use std::io::{self, prelude::*};
+use std::process::{Command as SysCommand, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub fn handle_shell_command(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
+    if block_binds(app) || app.archive.is_some() {
+        return;
+    }
+
+    // unlike the other prompts, commands are kept between sessions
+    if input.history(Command::RunShell).is_empty() {
+        input.set_history(Command::RunShell, read_history());
+    }
+
+    app.show_popup = true;
+    app.last_command = Some(Command::RunShell);
+    *input_active = true;
+}
+
+// `{}` is the highlighted item, `{s}` the move/copy buffer (or the
+// highlighted item) and `{d}` the current directory, all shell quoted
+pub fn expand(template: &str, item: &str, selection: &[String], dir: &str) -> String {
+    let selection = if selection.is_empty() {
+        quote(item)
+    } else {
+        selection
+            .iter()
+            .map(|path| quote(path))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    // a single pass, so names containing `{}` aren't expanded again
+    let mut result = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("{}") {
+            result.push_str(&quote(item));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{s}") {
+            result.push_str(&selection);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{d}") {
+            result.push_str(&quote(dir));
+            rest = after;
+        } else {
+            result.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn highlighted_item(app: &App) -> String {
+    if let Some(selected) = app.files.state.selected() {
+        if let Some(file) = app.files.items.get(selected) {
+            return file.0.clone();
+        }
+    }
+
+    if let Some(selected) = app.dirs.state.selected() {
+        if let Some(dir) = app.dirs.items.get(selected) {
+            return dir.0.trim_end_matches('/').to_string();
+        }
+    }
+
+    String::new()
+}
+
+// a leading `!` runs the command with the ui suspended, for anything
+// interactive, otherwise its output is shown in a popup
+pub fn run_shell_command(app: &mut App, input: &str) {
+    let input = input.trim();
+    if input.is_empty() {
+        return;
+    }
+
+    let (template, interactive) = match input.strip_prefix('!') {
+        Some(template) => (template, true),
+        None => (input, false),
+    };
+
+    let script = script(app, template);
+
+    if interactive {
+        app.run_in_terminal = Some(shell(&script));
+        return;
+    }
+
+    run_in_output(app, template.trim(), shell(&script), true);
+}
+
+// runs `command` on a job, its output goes into the popup as it's printed
+pub fn run_in_output(app: &mut App, title: &str, mut command: SysCommand, refresh: bool) {
+    let mut child = match command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            app.message = Some(format!("Failed to run {}: {}", title, err));
+            return;
+        }
+    };
+
+    let feed = Arc::new(Mutex::new(OutputFeed {
+        title: title.to_string(),
+        ..OutputFeed::default()
+    }));
+
+    app.output_title = format!("{} (running)", title);
+    app.output = vec![];
+    app.output_scroll = 0;
+    app.show_output = true;
+    app.output_feed = Some(feed.clone());
+
+    let mut job = Job::spawn(format!("Running {}", title), 0, move |_| {
+        let errors = child.stderr.take().map(|stderr| {
+            let feed = feed.clone();
+            thread::spawn(move || OutputFeed::read_from(&feed, stderr))
+        });
+
+        if let Some(stdout) = child.stdout.take() {
+            OutputFeed::read_from(&feed, stdout);
+        }
+        if let Some(errors) = errors {
+            let _ = errors.join();
+        }
+
+        let status = match child.wait()?.code() {
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        };
+        feed.lock().unwrap().status = Some(status);
+
+        Ok(())
+    });
+
+    job.refresh = refresh;
+    app.jobs.push(job);
+}
+
+// the template with its placeholders filled in from the current state
+pub fn script(app: &App, template: &str) -> String {
+    let dir = std::env::current_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let selection = [app.selected_files.clone(), app.selected_dirs.clone()].concat();
+
+    expand(template, &highlighted_item(app), &selection, &dir)
+}
+
+pub fn shell(script: &str) -> SysCommand {
+    let mut command = SysCommand::new("sh");
+    command.arg("-c").arg(script);
+    command
+}
+
+fn read_history() -> Vec<String> {
+    let path = match config_dir() {
+        Some(dir) => dir.join("traverse/command_history.txt"),
+        None => return vec![],
+    };
+
+    match fs::read_to_string(path) {
+        Ok(history) => history
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+pub fn write_history(app: &mut App, history: &[String]) {
+    if let Err(err) = save_history(history) {
+        app.message = Some(format!("Failed to save the command history: {}", err));
+    }
+}
+
+fn save_history(history: &[String]) -> io::Result<()> {
+    let dir = config_dir()
+        .ok_or_else(|| io::Error::other("no config directory"))?
+        .join("traverse");
+    fs::create_dir_all(&dir)?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(dir.join("command_history.txt"))?;
+
+    for command in history {
+        file.write_all(format!("{}\n", command).as_bytes())?;
+    }
+
+    Ok(())
+}
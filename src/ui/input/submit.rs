@@ -1,10 +1,11 @@
+use super::line_editor::LineEditor;
 use super::*;
 use crate::app::app::App;
 use crate::ui::display::pane::get_pwd;
 use run_app::Command;
 use std::path::PathBuf;
 
-pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool) {
+pub fn handle_submit(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
     if *input_active {
         if app.last_command == Some(Command::CreateFile) {
             App::create_file(&input);
@@ -21,40 +22,80 @@ pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool)
                 .0
                 .clone();
 
-            std::fs::rename(file, input.clone()).unwrap();
+            std::fs::rename(file, input.to_string()).unwrap();
             app.update_files();
             app.update_dirs();
             app.last_command = None;
         } else if app.last_command == Some(Command::RenameDir) {
             let dir = app.dirs.items[app.dirs.state.selected().unwrap()].0.clone();
 
-            std::fs::rename(dir, input.clone()).unwrap();
+            std::fs::rename(dir, input.to_string()).unwrap();
             app.update_dirs();
             app.update_files();
             app.last_command = None;
-        } else if app.last_command == Some(Command::ShowNav) {
-            let path = Some(PathBuf::from(input.clone()));
-
-            if path.is_some() {
-                std::env::set_current_dir(path.unwrap()).unwrap();
-
-                app.cur_dir = std::env::current_dir()
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-                    .to_string();
-
-                app.update_files();
-                app.update_dirs();
+        } else if app.last_command == Some(Command::CreateArchive) {
+            compress::create_archive(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::ExtractArchive) {
+            if let Some(selected) = app.files.state.selected() {
+                let file = app.files.items[selected].0.clone();
+
+                if extract::zip_password_required(&file) {
+                    // keep the popup open, now asking for the password
+                    app.extract_dest = Some(input.to_string());
+                    app.last_command = Some(Command::ZipPassword);
+                    input.clear();
+                    return;
+                }
 
-                app.show_popup = false;
-                app.show_nav = false;
-                app.last_command = None;
-            } else {
-                app.show_popup = false;
-                app.show_nav = false;
-                app.last_command = None;
+                if let Err(err) = extract::extract_archive(app, &file, input, None) {
+                    app.message = Some(format!("Failed to extract {}: {}", file, err));
+                }
             }
+            app.last_command = None;
+        } else if app.last_command == Some(Command::ZipPassword) {
+            if let (Some(selected), Some(dest)) =
+                (app.files.state.selected(), app.extract_dest.clone())
+            {
+                let file = app.files.items[selected].0.clone();
+
+                match extract::check_zip_password(&file, input) {
+                    Ok(true) => {
+                        if let Err(err) = extract::extract_archive(app, &file, &dest, Some(input)) {
+                            app.message = Some(format!("Failed to extract {}: {}", file, err));
+                        }
+                    }
+                    Ok(false) => {
+                        app.message = Some("Wrong password, try again".to_string());
+                        input.clear();
+                        return;
+                    }
+                    Err(err) => {
+                        app.message = Some(format!("Failed to extract {}: {}", file, err));
+                    }
+                }
+            }
+            app.extract_dest = None;
+            app.last_command = None;
+        } else if app.last_command == Some(Command::RunShell) {
+            shell::run_shell_command(app, input);
+            shell::write_history(app, input.history(Command::RunShell));
+            app.last_command = None;
+        } else if app.last_command == Some(Command::Filter) {
+            tabs::handle_filter_submit(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::ConfirmCustom) {
+            app.last_command = None;
+            custom::handle_confirm_submit(app, input);
+        } else if app.last_command == Some(Command::ShowNav) {
+            // a path that doesn't exist leaves the navigator open to fix it
+            if !nav::handle_nav_submit(app, input) {
+                return;
+            }
+
+            app.show_popup = false;
+            app.show_nav = false;
+            app.last_command = None;
         }
 
         input.clear();
@@ -63,7 +104,25 @@ pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool)
         app.update_files();
         app.update_dirs();
     } else {
+        if app.archive.is_some() {
+            archive::handle_archive_submit(app);
+            return;
+        }
+
+        if let Some(selected) = app.files.state.selected() {
+            if let Some(file) = app.files.items.get(selected) {
+                if archive::is_archive(&file.0) && !open::has_opener(app, &file.0) {
+                    archive::open_archive(app);
+                } else {
+                    open::handle_open(app);
+                }
+            }
+            return;
+        }
+
         if app.dirs.state.selected().is_some() {
+            jump::leave(app);
+
             if app.dirs.items[app.dirs.state.selected().unwrap()].0 == "../" {
                 let mut path = std::env::current_dir().unwrap();
                 path.pop();
@@ -79,6 +138,10 @@ pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool)
             app.update_files();
             app.update_dirs();
 
+            if jump::arrive(app) {
+                return;
+            }
+
             if let Some(selected) = app.files.state.selected() {
                 if selected >= app.files.items.len() {
                     if !app.files.items.is_empty() {
@@ -95,7 +158,7 @@ pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool)
     }
 }
 
-pub fn handle_open_fzf_result(app: &mut App, input: &mut String, input_active: &mut bool) {
+pub fn handle_open_fzf_result(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
     if app.fzf_results.state.selected().is_none() {
         return;
     } else {
@@ -105,7 +168,9 @@ pub fn handle_open_fzf_result(app: &mut App, input: &mut String, input_active: &
         {
             let path = app.fzf_results.items[app.fzf_results.state.selected().unwrap()].clone();
             let path = PathBuf::from(path).parent().unwrap().to_path_buf();
+            jump::leave(app);
             std::env::set_current_dir(path).unwrap();
+            app.archive = None;
 
             app.update_files();
             app.update_dirs();
@@ -124,6 +189,7 @@ pub fn handle_open_fzf_result(app: &mut App, input: &mut String, input_active: &
             app.dirs.state.select(None);
 
             app.cur_dir = get_pwd();
+            jump::arrive(app);
         }
     }
 }
@@ -139,7 +205,9 @@ pub fn handle_open_bookmark(app: &mut App) {
             let path =
                 app.bookmarked_dirs.items[app.bookmarked_dirs.state.selected().unwrap()].clone();
             let path = PathBuf::from(path);
+            jump::leave(app);
             std::env::set_current_dir(path).unwrap();
+            app.archive = None;
 
             app.update_files();
             app.update_dirs();
@@ -152,6 +220,7 @@ pub fn handle_open_bookmark(app: &mut App) {
             app.dirs.state.select(None);
 
             app.cur_dir = get_pwd();
+            jump::arrive(app);
         }
     }
 }
@@ -0,0 +1,313 @@
+use super::extract::{resolve_conflict, Conflict};
+use super::jump::{apply_position, capture_position};
+use super::line_editor::LineEditor;
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::app::job::Job;
+use crate::app::layout::PaneLayout;
+use crate::app::tab::Tab;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command as SysCommand;
+use std::sync::atomic::Ordering;
+
+// what the ops menu always has, tab operations come after
+const OPS: usize = 3;
+
+// the current tab's state, which lives in `App`, taken out of it
+pub fn take_current(app: &mut App) -> Tab {
+    Tab {
+        dir: std::env::current_dir().unwrap_or_default(),
+        position: Some(capture_position(app)),
+        archive: app.archive.take(),
+        filter: std::mem::take(&mut app.filter),
+        sort: app.sort,
+        back: std::mem::take(&mut app.history.back),
+        forward: std::mem::take(&mut app.history.forward),
+        listing: None,
+    }
+}
+
+// makes `tab`'s state the current one
+pub fn apply(app: &mut App, tab: Tab) {
+    // its directory may have been removed while it was in the background
+    let dir = tab
+        .dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    if let Err(err) = std::env::set_current_dir(&dir) {
+        app.message = Some(format!("Cannot open {}: {}", dir.display(), err));
+    }
+
+    app.archive = if dir == tab.dir { tab.archive } else { None };
+    app.filter = tab.filter;
+    app.sort = tab.sort;
+    app.history.back = tab.back;
+    app.history.forward = tab.forward;
+    app.cur_dir = get_pwd();
+
+    app.update_files();
+    app.update_dirs();
+
+    match tab.position {
+        Some(position) if dir == tab.dir => apply_position(app, &position),
+        _ => {
+            app.files.state.select(Some(0));
+            app.dirs.state.select(None);
+        }
+    }
+}
+
+// puts the current tab's state in its slot
+fn store(app: &mut App) {
+    let tab = take_current(app);
+    app.tabs[app.tab] = tab;
+}
+
+// makes the tab at `index` the current one, leaving only its directory in
+// the slot
+fn load(app: &mut App, index: usize) {
+    let placeholder = Tab::new(app.tabs[index].dir.clone());
+    let tab = std::mem::replace(&mut app.tabs[index], placeholder);
+    app.tab = index;
+
+    apply(app, tab);
+}
+
+pub fn handle_new_tab(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    store(app);
+
+    let dir = app.tabs[app.tab].dir.clone();
+    app.tabs.insert(app.tab + 1, Tab::new(dir));
+    load(app, app.tab + 1);
+}
+
+pub fn handle_close_tab(app: &mut App) {
+    if block_binds(app) || app.tabs.len() == 1 {
+        return;
+    }
+
+    app.tabs.remove(app.tab);
+    load(app, app.tab.min(app.tabs.len() - 1));
+}
+
+pub fn handle_cycle_tab(app: &mut App, idx: isize) {
+    if block_binds(app) || app.tabs.len() == 1 {
+        return;
+    }
+
+    let index = (app.tab as isize + idx).rem_euclid(app.tabs.len() as isize) as usize;
+
+    store(app);
+    load(app, index);
+}
+
+// `n` counts from 1, like the tab bar
+pub fn handle_goto_tab(app: &mut App, n: usize) {
+    if block_binds(app) || n == 0 || n > app.tabs.len() || n - 1 == app.tab {
+        return;
+    }
+
+    store(app);
+    load(app, n - 1);
+}
+
+// the ops menu gets a copy and a move to the other pane and to each of the
+// other tabs
+pub fn add_tab_operations(app: &mut App) {
+    app.ops_menu.items.truncate(OPS);
+    app.ops_destinations.clear();
+
+    if app.layout == PaneLayout::Dual {
+        if let Some(other) = &app.other {
+            app.ops_destinations
+                .push((other.dir.clone(), "the other pane".to_string()));
+        }
+    }
+
+    for (i, tab) in app.tabs.iter().enumerate() {
+        if i != app.tab {
+            app.ops_destinations
+                .push((tab.dir.clone(), format!("tab {} ({})", i + 1, tab.name())));
+        }
+    }
+
+    for (_, label) in &app.ops_destinations {
+        app.ops_menu.items.push(format!("Copy to {}", label));
+        app.ops_menu.items.push(format!("Move to {}", label));
+    }
+}
+
+// returns whether `selected` was one of the tab operations
+pub fn handle_tab_operation(app: &mut App, selected: usize) -> bool {
+    if selected < OPS {
+        return false;
+    }
+
+    let (dest, label) = match app.ops_destinations.get((selected - OPS) / 2) {
+        Some(destination) => destination.clone(),
+        None => return true,
+    };
+    let copy = (selected - OPS) % 2 == 0;
+
+    transfer(app, &dest, copy, &label);
+    app.show_ops_menu = false;
+    app.last_command = None;
+
+    true
+}
+
+// copies or moves the move/copy buffer, or the highlighted item when it's
+// empty, into `dest` on a background job. What's already there is skipped,
+// overwritten or kept with the new one renamed, as `transfer_conflict` says,
+// unless compare found it out of date
+pub fn transfer(app: &mut App, dest: &Path, copy: bool, label: &str) {
+    let items: Vec<String> = if app.selected_files.is_empty() {
+        highlighted(app).into_iter().collect()
+    } else {
+        app.selected_files.clone()
+    };
+
+    let mut planned = vec![];
+    let mut skipped = 0;
+
+    for item in items {
+        let source = PathBuf::from(item);
+        let target = match source.file_name() {
+            Some(name) => dest.join(name),
+            None => continue,
+        };
+
+        let conflict = if app.compared.contains(&(source.clone(), target.clone())) {
+            Conflict::Overwrite
+        } else {
+            app.transfer_conflict
+        };
+
+        // overwriting the source, or a directory it's in, would lose it
+        match resolve_conflict(target, conflict) {
+            Some(target) if !source.starts_with(&target) => planned.push((source, target)),
+            _ => skipped += 1,
+        }
+    }
+
+    app.selected_files = vec![];
+    app.selected_dirs = vec![];
+    app.compared = vec![];
+
+    if skipped > 0 {
+        app.message = Some(format!("Skipped {} already in {}", skipped, label));
+    }
+
+    if planned.is_empty() {
+        return;
+    }
+
+    let verb = if copy { "Copying" } else { "Moving" };
+    let total = planned.len() as u64;
+
+    let mut job = Job::spawn(format!("{} to {}", verb, label), total, move |progress| {
+        for (source, target) in planned {
+            transfer_one(&source, &target, copy)?;
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Ok(())
+    });
+
+    job.refresh = true;
+    app.jobs.push(job);
+}
+
+fn transfer_one(source: &Path, target: &Path, copy: bool) -> io::Result<()> {
+    // only there when overwriting, `cp -r` would copy a directory into the
+    // one that's there rather than over it
+    if let Ok(metadata) = fs::symlink_metadata(target) {
+        if metadata.is_dir() {
+            fs::remove_dir_all(target)?;
+        } else {
+            fs::remove_file(target)?;
+        }
+    }
+
+    let output = if copy {
+        SysCommand::new("cp")
+            .arg("-r")
+            .arg(source)
+            .arg(target)
+            .output()?
+    } else {
+        SysCommand::new("mv").arg(source).arg(target).output()?
+    };
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn highlighted(app: &App) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+
+    let name = if let Some(selected) = app.files.state.selected() {
+        &app.files.items.get(selected)?.0
+    } else {
+        &app.dirs.items.get(app.dirs.state.selected()?)?.0
+    };
+
+    if name == "../" {
+        return None;
+    }
+
+    Some(cwd.join(name).to_string_lossy().to_string())
+}
+
+pub fn handle_sort(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.sort = app.sort.next();
+    app.update_files();
+    app.update_dirs();
+}
+
+pub fn handle_filter(app: &mut App, input: &mut LineEditor, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::Filter);
+    input.set(app.filter.clone());
+}
+
+// an empty filter lists everything again
+pub fn handle_filter_submit(app: &mut App, input: &str) {
+    app.filter = input.trim().to_string();
+
+    app.update_files();
+    app.update_dirs();
+
+    if app.files.items.is_empty() {
+        app.files.state.select(None);
+        app.dirs.state.select(Some(0));
+    } else {
+        app.files.state.select(Some(0));
+        app.dirs.state.select(None);
+    }
+}
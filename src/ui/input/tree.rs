@@ -0,0 +1,130 @@
+use super::line_editor::LineEditor;
+use super::miller::{self, normalize, select};
+use crate::app::app::App;
+use crate::app::layout::PaneLayout;
+use crate::ui::display::block::block_binds;
+use std::path::Path;
+
+pub fn tree(app: &App) -> bool {
+    app.layout == PaneLayout::Tree
+}
+
+// the rows, the same way the miller layout lists its entries
+pub fn entries(app: &mut App) -> Vec<(String, bool)> {
+    // archives are shown flat, they aren't on disk to be walked
+    if app.archive.is_some() {
+        let dirs = app.dirs.items.iter().filter(|item| item.0 != "../");
+        let dirs = dirs.map(|item| (item.0.clone(), true));
+        let files = app.files.items.iter().map(|item| (item.0.clone(), false));
+
+        return dirs.chain(files).collect();
+    }
+
+    app.tree
+        .nodes
+        .iter()
+        .map(|node| (node.path.clone(), node.is_dir))
+        .collect()
+}
+
+pub fn handle_tree(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    // back to the configured layout, or the usual one if that's this one
+    app.layout = match (app.layout, app.configured_layout) {
+        (PaneLayout::Tree, PaneLayout::Tree) => PaneLayout::Default,
+        (PaneLayout::Tree, configured) => configured,
+        _ => PaneLayout::Tree,
+    };
+
+    app.update_listings();
+}
+
+// the highlighted row's path, and whether it's a directory
+fn highlighted(app: &mut App) -> Option<(String, bool)> {
+    let entries = entries(app);
+    let index = normalize(app, &entries)?;
+    entries.get(index).cloned()
+}
+
+fn set_expanded(app: &mut App, path: &str, expanded: bool) {
+    let path = std::env::current_dir().unwrap_or_default().join(path);
+
+    if expanded {
+        app.tree.expanded.insert(path);
+    } else {
+        app.tree.expanded.remove(&path);
+    }
+
+    app.update_listings();
+}
+
+fn is_expanded(app: &App, path: &str) -> bool {
+    let path = std::env::current_dir().unwrap_or_default().join(path);
+    app.tree.expanded.contains(&path)
+}
+
+// Space
+pub fn handle_toggle(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if let Some((path, true)) = highlighted(app) {
+        let expanded = is_expanded(app, &path);
+        set_expanded(app, &path, !expanded);
+    }
+}
+
+// Right, opens the highlighted directory, or goes into it when it's open
+pub fn handle_expand(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    match highlighted(app) {
+        Some((path, true)) if is_expanded(app, &path) => {
+            let entries = entries(app);
+            if let Some(index) = normalize(app, &entries) {
+                select(app, &entries, index + 1);
+            }
+        }
+        Some((path, true)) => set_expanded(app, &path, true),
+        _ => {}
+    }
+}
+
+// Left, closes the highlighted directory, otherwise goes to the directory
+// the row is in, and from the top level to the parent directory
+pub fn handle_collapse(app: &mut App, input: &mut LineEditor) {
+    if block_binds(app) {
+        return;
+    }
+
+    let (path, is_dir) = match highlighted(app) {
+        Some(highlighted) => highlighted,
+        None => return miller::handle_parent(app, input),
+    };
+
+    if is_dir && is_expanded(app, &path) {
+        set_expanded(app, &path, false);
+        return;
+    }
+
+    let parent = Path::new(&path)
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if parent.is_empty() {
+        miller::handle_parent(app, input);
+        return;
+    }
+
+    let entries = entries(app);
+    if let Some(index) = entries.iter().position(|entry| entry.0 == parent) {
+        select(app, &entries, index);
+    }
+}